@@ -0,0 +1,49 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use num_bigint::BigInt;
+use std::str::FromStr;
+
+use stark_verifier::{deserializer, verify_mimc_proof, MODULUS};
+use stark_verifier::air::MimcPublicInputs;
+use stark_verifier::fft::fft_in_place;
+use stark_verifier::field::{Field, FieldElement, Goldilocks};
+use stark_verifier::options::ProofOptions;
+use stark_verifier::proof::StarkProof;
+use stark_verifier::utils::mimc;
+
+fn verify(c: &mut Criterion) {
+    let bytes = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/proof.bin")).unwrap();
+    let proof: StarkProof = deserializer::from_bytes(&bytes).unwrap();
+    let constants: Vec<FieldElement> = (0..64u64).map(|i| FieldElement::from(i.pow(7) ^ 42)).collect();
+    let output = mimc(FieldElement::from(3u64), 8192, &constants);
+    let inputs = MimcPublicInputs::new(FieldElement::from(3u64), 8192, &constants, output);
+    let options = ProofOptions::legacy();
+
+    c.bench_function("verify_mimc_proof", |b| b.iter(|| {
+        verify_mimc_proof(&inputs, black_box(&proof), &options).unwrap()
+    }));
+}
+
+// the verifier used to reduce BigInt products by the modulus, this is what it is compared against
+fn field_mul(c: &mut Criterion) {
+    let a = FieldElement::from(3u64).pow(1000);
+    let b = FieldElement::from(7u64).pow(1000);
+
+    c.bench_function("mul montgomery", |bench| bench.iter(|| black_box(a) * black_box(b)));
+
+    let modulus = BigInt::from_str(MODULUS).unwrap();
+    let a_big = BigInt::from_str(&a.to_string()).unwrap();
+    let b_big = BigInt::from_str(&b.to_string()).unwrap();
+
+    c.bench_function("mul bigint", |bench| bench.iter(|| (black_box(&a_big) * black_box(&b_big)) % &modulus));
+}
+
+fn ntt(c: &mut Criterion) {
+    let n = 1 << 20;
+    let root = Goldilocks::get_root_of_unity(n);
+    let mut values: Vec<Goldilocks> = (0..n).map(Goldilocks::from).collect();
+
+    c.bench_function("fft 2^20 goldilocks", |b| b.iter(|| fft_in_place(black_box(&mut values), &root)));
+}
+
+criterion_group!(benches, verify, field_mul, ntt);
+criterion_main!(benches);
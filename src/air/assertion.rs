@@ -0,0 +1,104 @@
+use crate::error::VerifyError;
+use crate::field::Field;
+use crate::utils::is_power_of_2;
+
+/// A boundary constraint on a register of the execution trace
+#[derive(Clone, Debug, PartialEq)]
+pub enum Assertion<F: Field> {
+    /// the register takes `value` at `step`
+    Single { register: usize, step: usize, value: F },
+    /// the register takes `value` at `first_step` and every `stride` steps after it
+    Periodic { register: usize, first_step: usize, stride: usize, value: F },
+}
+
+impl<F: Field> Assertion<F> {
+    pub fn single(register: usize, step: usize, value: F) -> Self {
+        Assertion::Single { register, step, value }
+    }
+
+    pub fn periodic(register: usize, first_step: usize, stride: usize, value: F) -> Self {
+        Assertion::Periodic { register, first_step, stride, value }
+    }
+
+    pub fn register(&self) -> usize {
+        match self {
+            Assertion::Single { register, .. } => *register,
+            Assertion::Periodic { register, .. } => *register,
+        }
+    }
+
+    pub(crate) fn validate(&self, trace_width: usize, trace_length: usize) -> Result<(), VerifyError> {
+        if self.register() >= trace_width {
+            return Err(VerifyError::BadParameters("assertion outside of the trace"));
+        }
+
+        match self {
+            Assertion::Single { step, .. } if *step >= trace_length => Err(VerifyError::BadParameters("assertion outside of the trace")),
+            Assertion::Periodic { first_step, stride, .. } if !is_power_of_2(*stride) || *stride > trace_length || first_step >= stride => {
+                Err(VerifyError::BadParameters("periodic assertions need a power of 2 stride of at most the trace length, and a first step below it"))
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A periodic assertion as the verifier checks it: the steps it covers are the roots of
+/// x^num_steps - offset, so that B(x) = (P(x) - value) / (x^num_steps - offset) is a
+/// polynomial if the assertion holds.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PeriodicBoundary<F: Field> {
+    pub register: usize,
+    pub value: F,
+    pub num_steps: usize,
+    pub offset: F,
+}
+
+impl<F: Field> PeriodicBoundary<F> {
+    // `trace_root` generates the steps of the trace
+    pub fn new(register: usize, first_step: usize, stride: usize, value: F, trace_root: F, trace_length: usize) -> Self {
+        let num_steps = trace_length / stride;
+
+        PeriodicBoundary {
+            register,
+            value,
+            num_steps,
+            offset: trace_root.pow((first_step * num_steps) as u64),
+        }
+    }
+
+    pub fn zerofier<E: Field + From<F>>(&self, x: E) -> E {
+        x.pow(self.num_steps as u64) - E::from(self.offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::Goldilocks;
+
+    #[test]
+    fn test_validate() {
+        let one = Goldilocks::ONE;
+
+        assert!(Assertion::single(1, 63, one).validate(2, 64).is_ok());
+        assert!(Assertion::single(2, 0, one).validate(2, 64).is_err());
+        assert!(Assertion::single(0, 64, one).validate(2, 64).is_err());
+
+        assert!(Assertion::periodic(0, 3, 8, one).validate(1, 64).is_ok());
+        assert!(Assertion::periodic(0, 0, 64, one).validate(1, 64).is_ok());
+        assert!(Assertion::periodic(0, 8, 8, one).validate(1, 64).is_err());
+        assert!(Assertion::periodic(0, 0, 6, one).validate(1, 64).is_err());
+        assert!(Assertion::periodic(0, 0, 128, one).validate(1, 64).is_err());
+    }
+
+    #[test]
+    fn test_periodic_zerofier() {
+        let trace_root = Goldilocks::get_root_of_unity(64);
+        let boundary = PeriodicBoundary::new(0, 3, 8, Goldilocks::ONE, trace_root, 64);
+
+        // the zerofier vanishes exactly at steps 3, 11, 19, ...
+        for step in 0..64u64 {
+            assert_eq!(boundary.zerofier(trace_root.pow(step)).is_zero(), step % 8 == 3);
+        }
+    }
+}
@@ -0,0 +1,322 @@
+use std::convert::TryFrom;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use super::{Air, Assertion};
+use crate::error::VerifyError;
+use crate::field::Field;
+use crate::hash::AlgebraicHashField;
+
+/// A transition constraint as an expression over two consecutive rows of the trace, the
+/// periodic columns and the public inputs. Build them with the constructors and the
+/// arithmetic operators, e.g. `Expr::next(0) - Expr::current(0).pow(3) - Expr::periodic(0)`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr<F: Field> {
+    Constant(F),
+    /// a register at the current step
+    Current(usize),
+    /// a register at the next step
+    Next(usize),
+    Periodic(usize),
+    PublicInput(usize),
+    Add(Box<Expr<F>>, Box<Expr<F>>),
+    Sub(Box<Expr<F>>, Box<Expr<F>>),
+    Mul(Box<Expr<F>>, Box<Expr<F>>),
+    Neg(Box<Expr<F>>),
+    Pow(Box<Expr<F>>, u64),
+}
+
+impl<F: Field> Expr<F> {
+    pub fn constant(value: F) -> Self {
+        Expr::Constant(value)
+    }
+
+    pub fn current(register: usize) -> Self {
+        Expr::Current(register)
+    }
+
+    pub fn next(register: usize) -> Self {
+        Expr::Next(register)
+    }
+
+    pub fn periodic(column: usize) -> Self {
+        Expr::Periodic(column)
+    }
+
+    pub fn public_input(index: usize) -> Self {
+        Expr::PublicInput(index)
+    }
+
+    pub fn pow(self, exponent: u64) -> Self {
+        Expr::Pow(Box::new(self), exponent)
+    }
+
+    /// Degree of the expression with the registers and periodic values as degree 1, the
+    /// degree `Air::transition_degrees` expects. None if it doesn't fit a usize.
+    pub fn degree(&self) -> Option<usize> {
+        match self {
+            Expr::Constant(_) | Expr::PublicInput(_) => Some(0),
+            Expr::Current(_) | Expr::Next(_) | Expr::Periodic(_) => Some(1),
+            Expr::Add(a, b) | Expr::Sub(a, b) => Some(a.degree()?.max(b.degree()?)),
+            Expr::Mul(a, b) => a.degree()?.checked_add(b.degree()?),
+            Expr::Neg(a) => a.degree(),
+            Expr::Pow(a, exponent) => a.degree()?.checked_mul(usize::try_from(*exponent).ok()?),
+        }
+    }
+
+    /// The expression at a point of the evaluation domain or of an extension field E
+    pub fn evaluate<E: Field + From<F>>(&self, current: &[E], next: &[E], periodic_values: &[E], public_inputs: &[F]) -> E {
+        let eval = |e: &Expr<F>| e.evaluate(current, next, periodic_values, public_inputs);
+
+        match self {
+            Expr::Constant(value) => E::from(*value),
+            Expr::Current(i) => current[*i],
+            Expr::Next(i) => next[*i],
+            Expr::Periodic(i) => periodic_values[*i],
+            Expr::PublicInput(i) => E::from(public_inputs[*i]),
+            Expr::Add(a, b) => eval(a) + eval(b),
+            Expr::Sub(a, b) => eval(a) - eval(b),
+            Expr::Mul(a, b) => eval(a) * eval(b),
+            Expr::Neg(a) => -eval(a),
+            Expr::Pow(a, exponent) => eval(a).pow(*exponent),
+        }
+    }
+
+    // the largest register, periodic column and public input index used, plus one
+    fn bounds(&self) -> (usize, usize, usize) {
+        match self {
+            Expr::Constant(_) => (0, 0, 0),
+            Expr::Current(i) | Expr::Next(i) => (i + 1, 0, 0),
+            Expr::Periodic(i) => (0, i + 1, 0),
+            Expr::PublicInput(i) => (0, 0, i + 1),
+            Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) => {
+                let (a, b) = (a.bounds(), b.bounds());
+                (a.0.max(b.0), a.1.max(b.1), a.2.max(b.2))
+            },
+            Expr::Neg(a) | Expr::Pow(a, _) => a.bounds(),
+        }
+    }
+}
+
+impl<F: Field> Add for Expr<F> {
+    type Output = Expr<F>;
+
+    fn add(self, other: Expr<F>) -> Expr<F> {
+        Expr::Add(Box::new(self), Box::new(other))
+    }
+}
+
+impl<F: Field> Sub for Expr<F> {
+    type Output = Expr<F>;
+
+    fn sub(self, other: Expr<F>) -> Expr<F> {
+        Expr::Sub(Box::new(self), Box::new(other))
+    }
+}
+
+impl<F: Field> Mul for Expr<F> {
+    type Output = Expr<F>;
+
+    fn mul(self, other: Expr<F>) -> Expr<F> {
+        Expr::Mul(Box::new(self), Box::new(other))
+    }
+}
+
+impl<F: Field> Neg for Expr<F> {
+    type Output = Expr<F>;
+
+    fn neg(self) -> Expr<F> {
+        Expr::Neg(Box::new(self))
+    }
+}
+
+/// An Air defined by its transition constraints as expressions. The degrees come from
+/// the expressions and the public inputs are field elements the expressions can use.
+/// The fields are only changed through the methods, which keep every expression within
+/// the trace, the periodic columns and the public inputs.
+#[derive(Clone, Debug)]
+pub struct ExprAir<F: AlgebraicHashField> {
+    trace_length: usize,
+    trace_width: usize,
+    periodic_columns: Vec<Vec<F>>,
+    transitions: Vec<Expr<F>>,
+    assertions: Vec<Assertion<F>>,
+    public_inputs: Vec<F>,
+}
+
+impl<F: AlgebraicHashField> ExprAir<F> {
+    pub fn new(trace_length: usize, trace_width: usize, public_inputs: &[F]) -> Self {
+        ExprAir {
+            trace_length,
+            trace_width,
+            periodic_columns: Vec::new(),
+            transitions: Vec::new(),
+            assertions: Vec::new(),
+            public_inputs: public_inputs.to_vec(),
+        }
+    }
+
+    /// Adds a periodic column, the returned expression refers to it
+    pub fn add_periodic_column(&mut self, values: &[F]) -> Expr<F> {
+        self.periodic_columns.push(values.to_vec());
+        Expr::periodic(self.periodic_columns.len() - 1)
+    }
+
+    /// Adds a transition constraint, which has to vanish at every step but the last
+    pub fn add_transition(&mut self, constraint: Expr<F>) -> Result<(), VerifyError> {
+        let (registers, columns, inputs) = constraint.bounds();
+
+        if registers > self.trace_width {
+            return Err(VerifyError::BadParameters("the constraint uses a register outside of the trace"));
+        }
+
+        if columns > self.periodic_columns.len() {
+            return Err(VerifyError::BadParameters("the constraint uses an undefined periodic column"));
+        }
+
+        if inputs > self.public_inputs.len() {
+            return Err(VerifyError::BadParameters("the constraint uses an undefined public input"));
+        }
+
+        match constraint.degree() {
+            None => return Err(VerifyError::BadParameters("the degree of the constraint overflows")),
+            Some(0) => return Err(VerifyError::BadParameters("the constraint is constant, it doesn't depend on the trace")),
+            Some(_) => {},
+        }
+
+        self.transitions.push(constraint);
+        Ok(())
+    }
+
+    pub fn add_assertion(&mut self, assertion: Assertion<F>) {
+        self.assertions.push(assertion);
+    }
+}
+
+impl<F: AlgebraicHashField> Air for ExprAir<F> {
+    type BaseField = F;
+    type PublicInputs = Vec<F>;
+
+    fn trace_length(&self) -> usize {
+        self.trace_length
+    }
+
+    fn trace_width(&self) -> usize {
+        self.trace_width
+    }
+
+    fn periodic_columns(&self) -> Vec<Vec<F>> {
+        self.periodic_columns.clone()
+    }
+
+    fn transition_degrees(&self) -> Vec<usize> {
+        // add_transition only accepts constraints whose degree fits
+        self.transitions.iter().map(|t| t.degree().unwrap()).collect()
+    }
+
+    fn evaluate_transition<E: Field + From<F>>(&self, current: &[E], next: &[E], periodic_values: &[E], result: &mut [E]) {
+        for (r, t) in result.iter_mut().zip(&self.transitions) {
+            *r = t.evaluate(current, next, periodic_values, &self.public_inputs);
+        }
+    }
+
+    fn assertions(&self) -> Vec<Assertion<F>> {
+        self.assertions.clone()
+    }
+
+    fn public_inputs(&self) -> Vec<F> {
+        self.public_inputs.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::air::FibonacciAir;
+    use crate::error::VerifyError;
+    use crate::field::{Goldilocks, QuadExtension};
+    use crate::options::{HashFunction, ProofOptions};
+    use crate::proof::StarkProof;
+    use crate::prover::prove;
+    use crate::verify;
+
+    #[test]
+    fn test_expressions() {
+        let x = Goldilocks::from(5u64);
+        let e: Expr<Goldilocks> = Expr::next(1) - Expr::current(0).pow(3) * Expr::periodic(0) + Expr::constant(Goldilocks::ONE) - -Expr::public_input(0);
+
+        assert_eq!(e.degree(), Some(4));
+        assert_eq!(e.bounds(), (2, 1, 1));
+
+        let value = e.evaluate(&[x, Goldilocks::ZERO], &[Goldilocks::ZERO, Goldilocks::from(7u64)], &[Goldilocks::from(2u64)], &[Goldilocks::from(3u64)]);
+        assert_eq!(value, Goldilocks::from(7u64) - Goldilocks::from(250u64) + Goldilocks::from(4u64));
+
+        assert_eq!(Expr::<Goldilocks>::public_input(0).degree(), Some(0));
+        assert_eq!((Expr::<Goldilocks>::current(0) * Expr::next(0)).pow(2).degree(), Some(4));
+        assert_eq!(Expr::<Goldilocks>::current(0).pow(u64::MAX).pow(2).degree(), None);
+        assert_eq!((Expr::<Goldilocks>::current(0).pow(u64::MAX) * Expr::current(0)).degree(), None);
+    }
+
+    fn prove_and_verify(air: &ExprAir<Goldilocks>, trace: &[Vec<Goldilocks>]) -> Result<(), VerifyError> {
+        let options = ProofOptions::new(8, 40, 20, 4, HashFunction::Blake2s).unwrap();
+        let proof: StarkProof<QuadExtension<Goldilocks>> = prove(air, trace, &options)?;
+        verify(air, &proof, &options)
+    }
+
+    #[test]
+    fn test_expression_airs() {
+        let n = 256;
+
+        // fibonacci: (a, b) -> (b, a + b)
+        let fibonacci = FibonacciAir::new(n, Goldilocks::ZERO).trace();
+        let mut air = ExprAir::new(n, 2, &[fibonacci[1][n - 1]]);
+        air.add_transition(Expr::next(0) - Expr::current(1)).unwrap();
+        air.add_transition(Expr::next(1) - Expr::current(0) - Expr::current(1)).unwrap();
+        air.add_assertion(Assertion::single(0, 0, Goldilocks::ONE));
+        air.add_assertion(Assertion::single(1, 0, Goldilocks::ONE));
+        air.add_assertion(Assertion::single(1, n - 1, fibonacci[1][n - 1]));
+        assert_eq!(air.transition_degrees(), vec![1, 1]);
+        assert_eq!(prove_and_verify(&air, &fibonacci), Ok(()));
+
+        // counter: x -> x + step, with the step a public input
+        let step = Goldilocks::from(3u64);
+        let counter: Vec<Goldilocks> = (0..n as u64).map(|i| step * Goldilocks::from(i)).collect();
+        let counter_air = |step: Goldilocks| {
+            let mut air = ExprAir::new(n, 1, &[step]);
+            air.add_transition(Expr::next(0) - Expr::current(0) - Expr::public_input(0)).unwrap();
+            air.add_assertion(Assertion::single(0, 0, Goldilocks::ZERO));
+            air.add_assertion(Assertion::single(0, n - 1, counter[n - 1]));
+            air
+        };
+        assert_eq!(prove_and_verify(&counter_air(step), std::slice::from_ref(&counter)), Ok(()));
+
+        // another step doesn't match the trace
+        assert!(prove_and_verify(&counter_air(Goldilocks::from(4u64)), std::slice::from_ref(&counter)).is_err());
+
+        // hash chain: x -> (x + k)^3 with 16 round constants
+        let constants: Vec<Goldilocks> = (0..16u64).map(|i| Goldilocks::from(i.pow(7) ^ 42)).collect();
+        let mut chain = vec![Goldilocks::from(9u64)];
+        for i in 0..(n - 1) {
+            chain.push((chain[i] + constants[i % 16]).pow(3));
+        }
+
+        let mut air = ExprAir::new(n, 1, &[chain[0], chain[n - 1]]);
+        let k = air.add_periodic_column(&constants);
+        air.add_transition(Expr::next(0) - (Expr::current(0) + k).pow(3)).unwrap();
+        air.add_assertion(Assertion::single(0, 0, chain[0]));
+        air.add_assertion(Assertion::single(0, n - 1, chain[n - 1]));
+        assert_eq!(air.transition_degrees(), vec![3]);
+        assert_eq!(prove_and_verify(&air, &[chain]), Ok(()));
+    }
+
+    #[test]
+    fn test_undefined_references() {
+        let mut air = ExprAir::<Goldilocks>::new(64, 1, &[]);
+
+        assert_eq!(air.add_transition(Expr::next(1) - Expr::current(0)), Err(VerifyError::BadParameters("the constraint uses a register outside of the trace")));
+        assert_eq!(air.add_transition(Expr::next(0) - Expr::periodic(0)), Err(VerifyError::BadParameters("the constraint uses an undefined periodic column")));
+        assert_eq!(air.add_transition(Expr::next(0) - Expr::public_input(0)), Err(VerifyError::BadParameters("the constraint uses an undefined public input")));
+        assert_eq!(air.add_transition(Expr::next(0).pow(1 << 40).pow(1 << 40)), Err(VerifyError::BadParameters("the degree of the constraint overflows")));
+        assert_eq!(air.add_transition(Expr::constant(Goldilocks::ONE)), Err(VerifyError::BadParameters("the constraint is constant, it doesn't depend on the trace")));
+        assert!(air.transitions.is_empty());
+    }
+}
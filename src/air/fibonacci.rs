@@ -0,0 +1,63 @@
+use super::{Air, Assertion};
+use crate::field::Field;
+use crate::hash::AlgebraicHashField;
+
+/// Two registers stepping (a, b) -> (b, a + b) from (1, 1), so that the second register
+/// ends at the `num_steps + 1`-th Fibonacci number `result`.
+pub struct FibonacciAir<F: AlgebraicHashField> {
+    pub num_steps: usize,
+    pub result: F,
+}
+
+impl<F: AlgebraicHashField> FibonacciAir<F> {
+    pub fn new(num_steps: usize, result: F) -> Self {
+        FibonacciAir { num_steps, result }
+    }
+
+    /// The two registers at every step
+    pub fn trace(&self) -> Vec<Vec<F>> {
+        let mut a: Vec<F> = vec![F::ONE];
+        let mut b: Vec<F> = vec![F::ONE];
+
+        for i in 0..(self.num_steps - 1) {
+            a.push(b[i]);
+            b.push(a[i] + b[i]);
+        }
+
+        vec![a, b]
+    }
+}
+
+impl<F: AlgebraicHashField> Air for FibonacciAir<F> {
+    type BaseField = F;
+    type PublicInputs = Vec<F>;
+
+    fn trace_length(&self) -> usize {
+        self.num_steps
+    }
+
+    fn trace_width(&self) -> usize {
+        2
+    }
+
+    fn periodic_columns(&self) -> Vec<Vec<F>> {
+        Vec::new()
+    }
+
+    fn transition_degrees(&self) -> Vec<usize> {
+        vec![1, 1]
+    }
+
+    fn evaluate_transition<E: Field + From<F>>(&self, current: &[E], next: &[E], _periodic_values: &[E], result: &mut [E]) {
+        result[0] = next[0] - current[1];
+        result[1] = next[1] - current[0] - current[1];
+    }
+
+    fn assertions(&self) -> Vec<Assertion<F>> {
+        vec![Assertion::single(0, 0, F::ONE), Assertion::single(1, 0, F::ONE), Assertion::single(1, self.num_steps - 1, self.result)]
+    }
+
+    fn public_inputs(&self) -> Vec<F> {
+        vec![self.result]
+    }
+}
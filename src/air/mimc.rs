@@ -0,0 +1,90 @@
+use super::{Air, Assertion, PublicInputs};
+use crate::field::Field;
+use crate::hash::AlgebraicHashField;
+
+/// The statement of a MiMC proof: num_steps - 1 rounds with `round_constants` map `inp`
+/// to `output`
+#[derive(Clone, Debug, PartialEq)]
+pub struct MimcPublicInputs<F: Field> {
+    pub inp: F,
+    pub output: F,
+    pub num_steps: usize,
+    pub round_constants: Vec<F>,
+}
+
+impl<F: Field> MimcPublicInputs<F> {
+    pub fn new(inp: F, num_steps: usize, round_constants: &[F], output: F) -> Self {
+        MimcPublicInputs {
+            inp,
+            output,
+            num_steps,
+            round_constants: round_constants.to_vec(),
+        }
+    }
+}
+
+// the number of steps is bound as the trace length
+impl<F: Field> PublicInputs for MimcPublicInputs<F> {
+    fn to_bytes(&self) -> Vec<u8> {
+        [self.inp, self.output].iter().chain(self.round_constants.iter()).flat_map(|x| x.to_bytes_be()).collect()
+    }
+}
+
+/// num_steps - 1 rounds of x -> x^3 + k, with the round constants k repeating. The
+/// register starts at `inp` and ends at `output`.
+pub struct MimcAir<F: AlgebraicHashField> {
+    pub inputs: MimcPublicInputs<F>,
+}
+
+impl<F: AlgebraicHashField> MimcAir<F> {
+    pub fn new(inputs: MimcPublicInputs<F>) -> Self {
+        MimcAir { inputs }
+    }
+
+    /// The register at every step, the output of the public inputs isn't used
+    pub fn trace(&self) -> Vec<F> {
+        let inputs = &self.inputs;
+        let mut trace: Vec<F> = vec![inputs.inp];
+
+        for i in 0..(inputs.num_steps - 1) {
+            let prev = trace[i];
+            trace.push(prev * prev.square() + inputs.round_constants[i % inputs.round_constants.len()]);
+        }
+
+        trace
+    }
+}
+
+impl<F: AlgebraicHashField> Air for MimcAir<F> {
+    type BaseField = F;
+    type PublicInputs = MimcPublicInputs<F>;
+
+    fn trace_length(&self) -> usize {
+        self.inputs.num_steps
+    }
+
+    fn trace_width(&self) -> usize {
+        1
+    }
+
+    fn periodic_columns(&self) -> Vec<Vec<F>> {
+        vec![self.inputs.round_constants.clone()]
+    }
+
+    fn transition_degrees(&self) -> Vec<usize> {
+        vec![3]
+    }
+
+    // C(P(x)) = P(g * x) - P(x)^3 - K(x)
+    fn evaluate_transition<E: Field + From<F>>(&self, current: &[E], next: &[E], periodic_values: &[E], result: &mut [E]) {
+        result[0] = next[0] - current[0] * current[0].square() - periodic_values[0];
+    }
+
+    fn assertions(&self) -> Vec<Assertion<F>> {
+        vec![Assertion::single(0, 0, self.inputs.inp), Assertion::single(0, self.inputs.num_steps - 1, self.inputs.output)]
+    }
+
+    fn public_inputs(&self) -> MimcPublicInputs<F> {
+        self.inputs.clone()
+    }
+}
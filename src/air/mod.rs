@@ -0,0 +1,273 @@
+use crate::error::VerifyError;
+use crate::field::Field;
+use crate::hash::{AlgebraicHashField, Blake2s256, Hasher};
+use crate::merkle_tree::MerkleDigest;
+use crate::options::{ProofOptions, TranscriptMode};
+use crate::transcript::LEGACY_MAX_CHALLENGES;
+use crate::utils::{is_power_of_2, lagrange_interp, zpoly};
+
+mod assertion;
+mod expr;
+mod fibonacci;
+mod mimc;
+mod periodic;
+
+pub use self::assertion::Assertion;
+pub use self::expr::{Expr, ExprAir};
+pub(crate) use self::assertion::PeriodicBoundary;
+pub use self::fibonacci::FibonacciAir;
+pub use self::mimc::{MimcAir, MimcPublicInputs};
+pub use self::periodic::{PeriodicColumn, evaluate_periodic_columns};
+
+/// Statement of a computation that isn't part of its constraints
+pub trait PublicInputs {
+    /// Serialization bound into the transcript and the public input digest
+    fn to_bytes(&self) -> Vec<u8>;
+}
+
+// e.g. the inputs and outputs of a computation
+impl<F: Field> PublicInputs for Vec<F> {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.iter().flat_map(|x| x.to_bytes_be()).collect()
+    }
+}
+
+/// Algebraic intermediate representation of a computation: the constraints its execution
+/// trace has to satisfy. The prover commits to the low degree extension of the trace and
+/// the verifier checks the constraints at random points of it.
+pub trait Air: Sync {
+    type BaseField: AlgebraicHashField;
+    type PublicInputs: PublicInputs;
+
+    /// Number of steps of the execution trace, a power of 2
+    fn trace_length(&self) -> usize;
+
+    /// Number of registers of the execution trace
+    fn trace_width(&self) -> usize;
+
+    /// Cyclic sequences of values the constraints can use, e.g. round constants. Their
+    /// lengths are powers of 2 of at most the trace length and step i sees the value at
+    /// i mod length.
+    fn periodic_columns(&self) -> Vec<Vec<Self::BaseField>>;
+
+    /// Degree of each transition constraint, counting the registers and periodic values
+    /// as degree 1
+    fn transition_degrees(&self) -> Vec<usize>;
+
+    /// Evaluates the transition constraints given the registers at a point and at the
+    /// next step, and the periodic values at the point. They vanish at every step but the
+    /// last of a valid trace. The point is in the evaluation domain, or for DEEP proofs in
+    /// the extension field E the challenges are drawn from.
+    fn evaluate_transition<E: Field + From<Self::BaseField>>(&self, current: &[E], next: &[E], periodic_values: &[E], result: &mut [E]);
+
+    /// Boundary constraints on the registers, usually derived from the public inputs
+    fn assertions(&self) -> Vec<Assertion<Self::BaseField>>;
+
+    fn public_inputs(&self) -> Self::PublicInputs;
+}
+
+// Derives the generator of the extended evaluation domain and the root of unity of
+// the subgroup a periodic column of `period` values is interpolated over.
+pub(crate) fn evaluation_domain<F: Field>(trace_length: usize, period: usize, extension_factor: usize) -> Result<(F, F), VerifyError> {
+    if !is_power_of_2(trace_length) || !is_power_of_2(period) {
+        return Err(VerifyError::BadParameters("number of steps and periodic column lengths must be powers of 2"));
+    }
+
+    if period > trace_length {
+        return Err(VerifyError::BadParameters("periodic column longer than the trace"));
+    }
+
+    // positions in the extended domain are u32s, and the domain has to be a subgroup of the field
+    let precision = match trace_length.checked_mul(extension_factor) {
+        Some(p) if p <= u32::MAX as usize && p.trailing_zeros() <= F::TWO_ADICITY => p,
+        _ => return Err(VerifyError::BadParameters("too many steps")),
+    };
+
+    let g2 = F::get_root_of_unity(precision as u64);
+    let skips2 = trace_length / period;
+
+    let val = g2.pow((extension_factor*skips2) as u64);
+
+    Ok((g2, val))
+}
+
+/// What the prover and the verifier derive from an Air before looking at the trace.
+pub(crate) struct AirContext<F: Field> {
+    pub trace_length: usize,
+    pub extension_factor: usize,
+    pub precision: usize,
+    /// generator of the extended evaluation domain
+    pub root_of_unity: F,
+    /// the point of the last step, where the transitions aren't constrained
+    pub last_step_position: F,
+    pub num_constraints: usize,
+    /// the composition polynomial has degree less than this
+    pub degree_bound: usize,
+    pub periodic_columns: Vec<PeriodicColumn<F>>,
+    pub trace_width: usize,
+    /// per register, the polynomial interpolating its single assertions and the one
+    /// vanishing at their steps
+    pub boundary_interpolants: Vec<Vec<F>>,
+    pub boundary_zerofiers: Vec<Vec<F>>,
+    /// each periodic assertion has a B(x) of its own
+    pub periodic_boundaries: Vec<PeriodicBoundary<F>>,
+}
+
+impl<F: AlgebraicHashField> AirContext<F> {
+    pub fn new<A: Air<BaseField = F>>(air: &A, options: &ProofOptions) -> Result<Self, VerifyError> {
+        let trace_length = air.trace_length();
+        let extension_factor = options.extension_factor;
+        let (root_of_unity, _) = evaluation_domain::<F>(trace_length, 1, extension_factor)?;
+        let precision = trace_length * extension_factor;
+
+        let trace_width = air.trace_width();
+        if trace_width == 0 {
+            return Err(VerifyError::BadParameters("the trace has to have at least one register"));
+        }
+
+        let degrees = air.transition_degrees();
+        if degrees.is_empty() {
+            return Err(VerifyError::BadParameters("there has to be at least one transition constraint"));
+        }
+
+        if degrees.contains(&0) {
+            return Err(VerifyError::BadParameters("a transition constraint is constant, it has degree 0"));
+        }
+
+        // powers of 2 keep the FRI layers aligned, the prover's D(x) have degree (d - 1) * steps
+        let max_degree = degrees.iter().cloned().max().unwrap_or(1);
+        let degree_bound = (max_degree - 1).next_power_of_two().max(2) * trace_length;
+
+        if 2 * degree_bound > precision {
+            return Err(VerifyError::BadParameters("extension factor too small for the constraint degrees"));
+        }
+
+        let periodic_columns = air.periodic_columns().iter()
+            .map(|values| PeriodicColumn::new(values, trace_length, extension_factor))
+            .collect::<Result<Vec<PeriodicColumn<F>>, VerifyError>>()?;
+
+        let trace_root = root_of_unity.pow(extension_factor as u64);
+        let last_step_position = trace_root.pow((trace_length - 1) as u64);
+
+        let mut singles: Vec<(usize, usize, F)> = Vec::new();
+        let mut periodic_boundaries: Vec<PeriodicBoundary<F>> = Vec::new();
+
+        for assertion in air.assertions() {
+            assertion.validate(trace_width, trace_length)?;
+
+            match assertion {
+                Assertion::Single { register, step, value } => singles.push((register, step, value)),
+                Assertion::Periodic { register, first_step, stride, value } => {
+                    periodic_boundaries.push(PeriodicBoundary::new(register, first_step, stride, value, trace_root, trace_length));
+                },
+            }
+        }
+
+        // the coefficients of the random linear combination, see verify_with
+        let num_coefficients = 4 * trace_width + degrees.len() - 1 + 2 * periodic_boundaries.len();
+        if options.transcript == TranscriptMode::Legacy && num_coefficients > LEGACY_MAX_CHALLENGES {
+            return Err(VerifyError::BadParameters("too many constraint coefficients for the legacy transcript"));
+        }
+
+        let mut steps: Vec<(usize, usize)> = singles.iter().map(|(register, step, _)| (*register, *step)).collect();
+        steps.sort_unstable();
+        steps.dedup();
+        if steps.len() != singles.len() {
+            return Err(VerifyError::BadParameters("more than one assertion for the same register and step"));
+        }
+
+        let mut boundary_interpolants: Vec<Vec<F>> = Vec::new();
+        let mut boundary_zerofiers: Vec<Vec<F>> = Vec::new();
+
+        // a register without single assertions gets I(x) = 0 and Q(x) = 1, so B(x) = P(x)
+        for r in 0..trace_width {
+            let xs: Vec<F> = singles.iter().filter(|a| a.0 == r).map(|(_, step, _)| trace_root.pow(*step as u64)).collect();
+            let ys: Vec<F> = singles.iter().filter(|a| a.0 == r).map(|(_, _, value)| *value).collect();
+
+            boundary_interpolants.push(lagrange_interp(&xs, &ys));
+            boundary_zerofiers.push(zpoly(&xs));
+        }
+
+        Ok(AirContext {
+            trace_length,
+            extension_factor,
+            precision,
+            root_of_unity,
+            last_step_position,
+            num_constraints: degrees.len(),
+            degree_bound,
+            periodic_columns,
+            trace_width,
+            boundary_interpolants,
+            boundary_zerofiers,
+            periodic_boundaries,
+        })
+    }
+
+    pub fn periodic_values<E: Field + From<F>>(&self, x: E) -> Vec<E> {
+        evaluate_periodic_columns(&self.periodic_columns, x)
+    }
+
+    /// (x^steps - 1) / (x - last_step_position), vanishing at every step but the last
+    pub fn transition_zerofier<E: Field + From<F>>(&self, x: E) -> E {
+        (x.pow(self.trace_length as u64) - E::ONE) * (x - E::from(self.last_step_position)).inv()
+    }
+
+    /// number of elements in each leaf of the trace commitment: the registers, one D(x)
+    /// per transition constraint, one B(x) per register and one per periodic assertion
+    pub fn leaf_width(&self) -> usize {
+        self.trace_width + self.num_constraints + self.trace_width + self.periodic_boundaries.len()
+    }
+}
+
+/// Digest of an Air's statement for proof headers: the trace length and the public
+/// inputs, hashed with Blake2s whatever the proof's hash function
+pub fn public_input_digest<A: Air>(air: &A) -> MerkleDigest {
+    let bytes = [&(air.trace_length() as u64).to_be_bytes()[..], &air.public_inputs().to_bytes()].concat();
+    Blake2s256::hash_leaf(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldElement;
+
+    #[test]
+    fn test_mimc_context() {
+        let constants: Vec<FieldElement> = (0..64u64).map(FieldElement::from).collect();
+        let air = MimcAir::new(MimcPublicInputs::new(FieldElement::from(3u64), 8192, &constants, FieldElement::ZERO));
+        let context = AirContext::new(&air, &ProofOptions::default()).unwrap();
+
+        // cubic constraints, so the D(x) have degree 2 * steps
+        assert_eq!(context.degree_bound, 2 * 8192);
+        assert_eq!(context.leaf_width(), 3);
+        assert_eq!(context.transition_zerofier(context.last_step_position.pow(2)), FieldElement::ZERO);
+
+        // the constants repeat every 64 steps
+        let x = context.root_of_unity.pow(8);
+        assert_eq!(context.periodic_values(x), vec![constants[1]]);
+
+        let short = MimcAir::new(MimcPublicInputs::new(FieldElement::from(3u64), 4, &constants[..4], FieldElement::ZERO));
+        assert!(AirContext::new(&short, &ProofOptions::default()).is_ok());
+
+        let options = ProofOptions { extension_factor: 2, ..ProofOptions::default() };
+        assert_eq!(AirContext::new(&air, &options).err(), Some(VerifyError::BadParameters("extension factor too small for the constraint degrees")));
+
+        let unconstrained = ExprAir::<FieldElement>::new(64, 1, &[]);
+        assert_eq!(AirContext::new(&unconstrained, &ProofOptions::default()).err(), Some(VerifyError::BadParameters("there has to be at least one transition constraint")));
+    }
+
+    #[test]
+    fn test_legacy_coefficient_limit() {
+        // 4 coefficients per register, the legacy transcript can't number more than 255
+        let air_of_width = |width: usize| {
+            let mut air = ExprAir::<FieldElement>::new(64, width, &[]);
+            air.add_transition(Expr::next(0) - Expr::current(0)).unwrap();
+            air
+        };
+
+        assert!(AirContext::new(&air_of_width(64), &ProofOptions::default()).is_ok());
+        assert_eq!(AirContext::new(&air_of_width(64), &ProofOptions::legacy()).err(), Some(VerifyError::BadParameters("too many constraint coefficients for the legacy transcript")));
+        assert!(AirContext::new(&air_of_width(63), &ProofOptions::legacy()).is_ok());
+    }
+}
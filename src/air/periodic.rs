@@ -0,0 +1,98 @@
+use super::evaluation_domain;
+use crate::error::VerifyError;
+use crate::fft::{fft_in_place, fft_inv};
+use crate::field::Field;
+use crate::utils::eval_poly_at;
+
+/// A cyclic sequence of values, step i of the trace sees the value at i mod its length.
+/// It is interpolated once over the subgroup of its length: the value at step i is then
+/// the polynomial at x^(steps / length) for x the point of step i.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeriodicColumn<F: Field> {
+    /// coefficients of the polynomial
+    pub poly: Vec<F>,
+    /// the column repeats every `cycle_length` steps
+    pub cycle_length: usize,
+    // steps / cycle_length, the power of x the polynomial is evaluated at
+    skips: usize,
+}
+
+impl<F: Field> PeriodicColumn<F> {
+    pub fn new(values: &[F], trace_length: usize, extension_factor: usize) -> Result<Self, VerifyError> {
+        let (_, root) = evaluation_domain::<F>(trace_length, values.len(), extension_factor)?;
+
+        Ok(PeriodicColumn {
+            poly: fft_inv(values, &root),
+            cycle_length: values.len(),
+            skips: trace_length / values.len(),
+        })
+    }
+
+    /// The column at a point x of the extended domain, or of an extension field
+    pub fn evaluate<E: Field + From<F>>(&self, x: E) -> E {
+        eval_poly_at(&self.poly, x.pow(self.skips as u64))
+    }
+
+    /// The column at the first `cycle_length * extension_factor` powers of `root_of_unity`,
+    /// the generator of the extended domain. The evaluations repeat after that.
+    pub fn extend(&self, root_of_unity: F, extension_factor: usize) -> Vec<F> {
+        let mut extension = self.poly.clone();
+        extension.resize(self.cycle_length * extension_factor, F::ZERO);
+        fft_in_place(&mut extension, &root_of_unity.pow(self.skips as u64));
+        extension
+    }
+}
+
+/// Evaluates every column at x, computing x^(steps / length) once per cycle length
+pub fn evaluate_periodic_columns<F: Field, E: Field + From<F>>(columns: &[PeriodicColumn<F>], x: E) -> Vec<E> {
+    let mut powers: Vec<(usize, E)> = Vec::new();
+
+    columns.iter().map(|column| {
+        let x_to_the_skips = match powers.iter().find(|(skips, _)| *skips == column.skips) {
+            Some((_, power)) => *power,
+            None => {
+                let power = x.pow(column.skips as u64);
+                powers.push((column.skips, power));
+                power
+            },
+        };
+
+        eval_poly_at(&column.poly, x_to_the_skips)
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::Goldilocks;
+
+    #[test]
+    fn test_periodic_columns() {
+        let (steps, extension_factor) = (64, 8);
+        let (g2, _) = evaluation_domain::<Goldilocks>(steps, 1, extension_factor).unwrap();
+
+        let columns: Vec<PeriodicColumn<Goldilocks>> = [1usize, 4, 16, 64].iter().map(|len| {
+            let values: Vec<Goldilocks> = (0..*len as u64).map(|i| Goldilocks::from(i * i + 7)).collect();
+            PeriodicColumn::new(&values, steps, extension_factor).unwrap()
+        }).collect();
+
+        // at the point of step i each column takes its value at i mod its length
+        for step in [0usize, 1, 5, 23, 63].iter() {
+            let x = g2.pow((step * extension_factor) as u64);
+            let expected: Vec<Goldilocks> = columns.iter().map(|c| Goldilocks::from(((step % c.cycle_length) as u64).pow(2) + 7)).collect();
+            assert_eq!(evaluate_periodic_columns(&columns, x), expected);
+        }
+
+        for column in &columns {
+            let extension = column.extend(g2, extension_factor);
+            assert_eq!(extension.len(), column.cycle_length * extension_factor);
+
+            for i in [0usize, 3, 17, 300].iter() {
+                assert_eq!(extension[i % extension.len()], column.evaluate(g2.pow(*i as u64)));
+            }
+        }
+
+        assert!(PeriodicColumn::new(&[Goldilocks::ONE; 3], steps, extension_factor).is_err());
+        assert!(PeriodicColumn::<Goldilocks>::new(&[], steps, extension_factor).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+use crate::deserializer::{self, read_exact, read_u32, _stark_proof_from_bytes};
+use crate::error::DeserializeError;
+use crate::field::{Field, FieldElement};
+use crate::merkle_tree::MerkleDigest;
+use crate::options::{HashFunction, ProofOptions, TranscriptMode};
+use crate::proof::StarkProof;
+use crate::serializer;
+
+/// First bytes of every proof container
+pub const MAGIC: [u8; 4] = *b"STRK";
+
+/// Version of the container format written by `ProofContainer::to_bytes`
+pub const VERSION: u32 = 1;
+
+// the header carries a public input digest
+const HAS_PUBLIC_INPUT_DIGEST: u32 = 1;
+
+/// A proof with a header describing what it proves and how to verify it. The layout is
+/// MAGIC, then little endian u32s like in the proof itself: the version, the field id of
+/// the proof's elements, the hash function, the transcript mode, the extension factor,
+/// the numbers of queries and FRI queries, the FRI folding factor and flags. If bit 0 of
+/// the flags is set the 32 byte public input digest follows, then the proof as
+/// `serializer::to_bytes` writes it.
+pub struct ProofContainer<F: Field = FieldElement> {
+    pub options: ProofOptions,
+    /// identifies the public inputs, absent for proofs converted from the legacy format
+    pub public_input_digest: Option<MerkleDigest>,
+    pub proof: StarkProof<F>,
+}
+
+impl<F: Field> ProofContainer<F> {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut output: Vec<u8> = MAGIC.to_vec();
+        let flags = if self.public_input_digest.is_some() { HAS_PUBLIC_INPUT_DIGEST } else { 0 };
+
+        let header = [
+            VERSION,
+            F::FIELD_ID,
+            self.options.hash_fn.id(),
+            self.options.transcript.id(),
+            self.options.extension_factor as u32,
+            self.options.num_queries as u32,
+            self.options.num_fri_queries as u32,
+            self.options.fri_folding_factor as u32,
+            flags,
+        ];
+
+        for value in header.iter() {
+            output.extend_from_slice(&value.to_le_bytes());
+        }
+
+        if let Some(digest) = &self.public_input_digest {
+            output.extend_from_slice(digest);
+        }
+
+        output.extend_from_slice(&serializer::to_bytes(&self.proof));
+        output
+    }
+}
+
+/// Parses a proof container over the field `F` whose header has the `options` the verifier
+/// expects, anything else is rejected before the proof is parsed
+pub fn from_bytes<F: Field>(bytes: &[u8], options: &ProofOptions) -> Result<ProofContainer<F>, DeserializeError> {
+    let mut magic = [0u8; 4];
+    read_exact(bytes, &mut magic, 0).map_err(|_| DeserializeError::BadMagic)?;
+
+    if magic != MAGIC {
+        return Err(DeserializeError::BadMagic);
+    }
+
+    let version = read_u32(bytes, 4)?;
+    if version != VERSION {
+        return Err(DeserializeError::UnsupportedVersion { version });
+    }
+
+    let field_id = read_u32(bytes, 8)?;
+    if field_id != F::FIELD_ID {
+        return Err(DeserializeError::FieldMismatch { expected: F::FIELD_ID, actual: field_id });
+    }
+
+    let hash_id = read_u32(bytes, 12)?;
+    let hash_fn = HashFunction::from_id(hash_id).ok_or(DeserializeError::UnknownIdentifier { offset: 12, id: hash_id })?;
+
+    let transcript_id = read_u32(bytes, 16)?;
+    let transcript = TranscriptMode::from_id(transcript_id).ok_or(DeserializeError::UnknownIdentifier { offset: 16, id: transcript_id })?;
+
+    let header_options = ProofOptions {
+        extension_factor: read_u32(bytes, 20)? as usize,
+        num_queries: read_u32(bytes, 24)? as usize,
+        num_fri_queries: read_u32(bytes, 28)? as usize,
+        fri_folding_factor: read_u32(bytes, 32)? as usize,
+        hash_fn,
+        transcript,
+    };
+
+    if header_options != *options {
+        return Err(DeserializeError::OptionsMismatch);
+    }
+
+    let flags = read_u32(bytes, 36)?;
+    if flags & !HAS_PUBLIC_INPUT_DIGEST != 0 {
+        return Err(DeserializeError::UnknownIdentifier { offset: 36, id: flags });
+    }
+
+    let mut offset = 40;
+    let mut public_input_digest = None;
+
+    if flags & HAS_PUBLIC_INPUT_DIGEST != 0 {
+        let mut digest = [0u8; 32];
+        offset += read_exact(bytes, &mut digest, offset)?;
+        public_input_digest = Some(digest);
+    }
+
+    let (proof, offset) = _stark_proof_from_bytes(bytes, offset)?;
+
+    if (offset as usize) != bytes.len() {
+        return Err(DeserializeError::TrailingBytes { offset });
+    }
+
+    Ok(ProofContainer { options: header_options, public_input_digest, proof })
+}
+
+/// Parses a headerless proof like proof.bin. Its options are those of the python prover,
+/// and nothing ties it to public inputs.
+pub fn from_legacy_bytes<F: Field>(bytes: &[u8]) -> Result<ProofContainer<F>, DeserializeError> {
+    Ok(ProofContainer {
+        options: ProofOptions::legacy(),
+        public_input_digest: None,
+        proof: deserializer::from_bytes(bytes)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{verify_mimc_container, mimc_public_input_digest};
+    use crate::air::MimcPublicInputs;
+    use crate::error::VerifyError;
+    use crate::field::Goldilocks;
+    use crate::utils::mimc;
+
+    fn legacy_bytes() -> Vec<u8> {
+        std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/proof.bin")).unwrap()
+    }
+
+    fn constants() -> Vec<FieldElement> {
+        (0..64u64).map(|i| FieldElement::from(i.pow(7) ^ 42)).collect()
+    }
+
+    fn verify(container: &ProofContainer, inp: u64) -> Result<(), VerifyError> {
+        let inp = FieldElement::from(inp);
+        verify_mimc_container(&MimcPublicInputs::new(inp, 8192, &constants(), mimc(inp, 8192, &constants())), container, &ProofOptions::legacy())
+    }
+
+    #[test]
+    fn test_legacy_proof() {
+        let container = from_legacy_bytes::<FieldElement>(&legacy_bytes()).unwrap();
+        assert_eq!(verify(&container, 3), Ok(()));
+
+        assert_eq!(from_bytes::<FieldElement>(&legacy_bytes(), &ProofOptions::legacy()).err(), Some(DeserializeError::BadMagic));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut container = from_legacy_bytes::<FieldElement>(&legacy_bytes()).unwrap();
+        let inp = FieldElement::from(3u64);
+        container.public_input_digest = Some(mimc_public_input_digest(&MimcPublicInputs::new(inp, 8192, &constants(), mimc(inp, 8192, &constants()))));
+
+        let bytes = container.to_bytes();
+        assert_eq!(&bytes[..4], b"STRK");
+        assert_eq!(&bytes[72..], &legacy_bytes()[..]);
+
+        let parsed = from_bytes::<FieldElement>(&bytes, &ProofOptions::legacy()).unwrap();
+        assert_eq!(parsed.options, container.options);
+        assert_eq!(parsed.to_bytes(), bytes);
+        assert_eq!(verify(&parsed, 3), Ok(()));
+
+        // the digest is for input 3
+        assert_eq!(verify(&parsed, 4), Err(VerifyError::PublicInputMismatch));
+
+        container.public_input_digest = None;
+        assert_eq!(from_bytes::<FieldElement>(&container.to_bytes(), &ProofOptions::legacy()).unwrap().public_input_digest, None);
+    }
+
+    #[test]
+    fn test_header_options() {
+        // a header with fewer queries is rejected before the proof is parsed
+        let mut bytes = from_legacy_bytes::<FieldElement>(&legacy_bytes()).unwrap().to_bytes();
+        bytes[24] = 40;
+        assert_eq!(from_bytes::<FieldElement>(&bytes, &ProofOptions::legacy()).err(), Some(DeserializeError::OptionsMismatch));
+        assert_eq!(from_bytes::<FieldElement>(&bytes[..40], &ProofOptions::legacy()).err(), Some(DeserializeError::OptionsMismatch));
+
+        // the header can't weaken the options the verifier expects
+        let mut container = from_legacy_bytes::<FieldElement>(&legacy_bytes()).unwrap();
+        container.options.num_queries = 1;
+        container.options.num_fri_queries = 1;
+        assert_eq!(verify(&container, 3), Err(VerifyError::OptionsMismatch));
+
+        // nor drop the public inputs of a labeled proof
+        let mut container = from_legacy_bytes::<FieldElement>(&legacy_bytes()).unwrap();
+        container.options.transcript = TranscriptMode::Labeled;
+        let inp = FieldElement::from(3u64);
+        let inputs = MimcPublicInputs::new(inp, 8192, &constants(), mimc(inp, 8192, &constants()));
+        assert_eq!(verify_mimc_container(&inputs, &container, &container.options.clone()), Err(VerifyError::MissingPublicInputDigest));
+    }
+
+    #[test]
+    fn test_bad_headers() {
+        let container = from_legacy_bytes::<FieldElement>(&legacy_bytes()).unwrap();
+        let bytes = container.to_bytes();
+
+        assert_eq!(from_bytes::<Goldilocks>(&bytes, &ProofOptions::legacy()).err(), Some(DeserializeError::FieldMismatch { expected: 0x0102, actual: 0x0101 }));
+        assert_eq!(from_bytes::<FieldElement>(&bytes[..2], &ProofOptions::legacy()).err(), Some(DeserializeError::BadMagic));
+
+        let mut bad = bytes.clone();
+        bad[4] = 2;
+        assert_eq!(from_bytes::<FieldElement>(&bad, &ProofOptions::legacy()).err(), Some(DeserializeError::UnsupportedVersion { version: 2 }));
+
+        let mut bad = bytes.clone();
+        bad[12] = 99;
+        assert_eq!(from_bytes::<FieldElement>(&bad, &ProofOptions::legacy()).err(), Some(DeserializeError::UnknownIdentifier { offset: 12, id: 99 }));
+
+        let mut bad = bytes.clone();
+        bad.push(0);
+        assert_eq!(from_bytes::<FieldElement>(&bad, &ProofOptions::legacy()).err(), Some(DeserializeError::TrailingBytes { offset: bytes.len() as u32 }));
+    }
+}
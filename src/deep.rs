@@ -0,0 +1,221 @@
+use crate::{air_transcript, read_element, verify_low_degree_proof, FirstLayer};
+use crate::air::{Air, AirContext};
+use crate::error::VerifyError;
+use crate::field::Field;
+use crate::hash::{Hasher, Blake2s256, Blake2b256, Sha256, Keccak256, Blake3, RescueLike, PoseidonLike};
+use crate::options::{HashFunction, ProofOptions, TranscriptMode};
+use crate::proof::DeepProof;
+use crate::transcript::Transcript;
+use crate::utils::eval_poly_at;
+
+// The DEEP variant of the protocol. The prover commits to the trace and to the constraint
+// composition H(x), a random combination of the constraint quotients:
+//
+//   H(x) = sum a_j C_j(x) / Z(x) + sum b_r (P_r(x) - I_r(x)) / Q_r(x) + sum c_i (P(x) - v_i) / (x^k_i - o_i)
+//
+// It then sends the registers at an out-of-domain point z and at g * z and H(z), and
+// FRI tests the DEEP composition
+//
+//   D(x) = sum d_r (P_r(x) - P_r(z)) / (x - z) + e_r (P_r(x) - P_r(g * z)) / (x - g * z) + f (H(x) - H(z)) / (x - z)
+//
+// which is only of low degree if the values at z are the ones of the committed polynomials.
+// D(x) isn't committed, the verifier computes it at the positions the first FRI layer
+// queries from openings of the trace and of H(x).
+
+// the transcript both sides start the DEEP protocol from
+pub(crate) fn deep_transcript<A: Air, H: Hasher>(air: &A, context: &AirContext<A::BaseField>, options: &ProofOptions) -> Result<Transcript<H>, VerifyError> {
+    // the legacy transcript ignores the out-of-domain values
+    if options.transcript != TranscriptMode::Labeled {
+        return Err(VerifyError::BadParameters("DEEP proofs need the labeled transcript"));
+    }
+
+    // D(x) is only bound to the trace through the first FRI layer
+    if context.degree_bound <= 16 {
+        return Err(VerifyError::BadParameters("too few steps for a DEEP proof"));
+    }
+
+    let mut transcript = air_transcript::<A, H>(air, options);
+    transcript.absorb(b"variant", b"deep");
+
+    Ok(transcript)
+}
+
+// one coefficient per transition constraint, per register and per periodic assertion
+pub(crate) fn num_composition_coefficients<F: Field>(context: &AirContext<F>) -> usize {
+    context.num_constraints + context.trace_width + context.periodic_boundaries.len()
+}
+
+// H(z) from the registers at z and g * z
+fn composition_at<A: Air, E: Field + From<A::BaseField>>(air: &A, context: &AirContext<A::BaseField>, coefficients: &[E], z: E, current: &[E], next: &[E]) -> E {
+    let (transition_coefficients, rest) = coefficients.split_at(context.num_constraints);
+    let (boundary_coefficients, periodic_coefficients) = rest.split_at(context.trace_width);
+
+    let mut c_of_z = vec![E::ZERO; context.num_constraints];
+    air.evaluate_transition(current, next, &context.periodic_values(z), &mut c_of_z);
+
+    let inv_zerofier = context.transition_zerofier(z).inv();
+    let mut h = E::ZERO;
+
+    for (a, c) in transition_coefficients.iter().zip(&c_of_z) {
+        h += *a * *c * inv_zerofier;
+    }
+
+    for (r, b) in boundary_coefficients.iter().enumerate() {
+        let interpolant: E = eval_poly_at(&context.boundary_interpolants[r], z);
+        let zerofier: E = eval_poly_at(&context.boundary_zerofiers[r], z);
+        h += *b * (current[r] - interpolant) * zerofier.inv();
+    }
+
+    for (c, boundary) in periodic_coefficients.iter().zip(&context.periodic_boundaries) {
+        h += *c * (current[boundary.register] - E::from(boundary.value)) * boundary.zerofier(z).inv();
+    }
+
+    h
+}
+
+/// Verifies a DEEP proof that an execution trace satisfies the constraints of `air`, see
+/// `prover::prove_deep`. The proof needs `TranscriptMode::Labeled`, and FRI's queries
+/// take the place of the spot checks so `options.num_queries` isn't used.
+pub fn verify_deep<A: Air, E: Field + From<A::BaseField>>(air: &A, proof: &DeepProof<E>, options: &ProofOptions) -> Result<(), VerifyError> {
+    options.validate()?;
+
+    match options.hash_fn {
+        HashFunction::Blake2s => verify_deep_with::<A, E, Blake2s256>(air, proof, options),
+        HashFunction::Blake2b => verify_deep_with::<A, E, Blake2b256>(air, proof, options),
+        HashFunction::Sha256 => verify_deep_with::<A, E, Sha256>(air, proof, options),
+        HashFunction::Keccak256 => verify_deep_with::<A, E, Keccak256>(air, proof, options),
+        HashFunction::Blake3 => verify_deep_with::<A, E, Blake3>(air, proof, options),
+        HashFunction::RescueLike => verify_deep_with::<A, E, RescueLike<A::BaseField>>(air, proof, options),
+        HashFunction::PoseidonLike => verify_deep_with::<A, E, PoseidonLike<A::BaseField>>(air, proof, options),
+    }
+}
+
+fn verify_deep_with<A: Air, E: Field + From<A::BaseField>, H: Hasher>(air: &A, proof: &DeepProof<E>, options: &ProofOptions) -> Result<(), VerifyError> {
+    let context = AirContext::new(air, options)?;
+    let mut transcript = deep_transcript::<A, H>(air, &context, options)?;
+
+    let width = context.trace_width;
+    let g2 = context.root_of_unity;
+    let depth = context.precision.trailing_zeros() as usize;
+
+    transcript.absorb_commitment(b"trace", &proof.trace_root);
+    let coefficients: Vec<E> = (0..num_composition_coefficients(&context)).map(|_| transcript.challenge_element(b"composition-coefficient")).collect();
+
+    transcript.absorb_commitment(b"composition", &proof.composition_root);
+    let z: E = transcript.challenge_element(b"ood-point");
+
+    if proof.ood_frame.len() != 2 * width {
+        return Err(VerifyError::OutOfDomainFrame { expected: 2 * width, actual: proof.ood_frame.len() });
+    }
+
+    transcript.absorb_elements(b"ood-frame", &proof.ood_frame);
+    transcript.absorb_elements(b"ood-composition", &[proof.ood_composition]);
+
+    let (current, next) = proof.ood_frame.split_at(width);
+
+    // Check the constraints once, at z
+    if composition_at(air, &context, &coefficients, z, current, next) != proof.ood_composition {
+        return Err(VerifyError::OutOfDomainConstraint);
+    }
+
+    let deep_coefficients: Vec<E> = (0..2 * width + 1).map(|_| transcript.challenge_element(b"deep-coefficient")).collect();
+    let g_z = z * E::from(g2.pow(context.extension_factor as u64));
+
+    let deep_values = |positions: &[u32]| -> Result<Vec<E>, VerifyError> {
+        let rows = proof.trace_branches.verify::<H>(positions, Some(proof.trace_root), 0, depth, options.transcript, options.fri_folding_factor)?;
+        let compositions = proof.composition_branches.verify::<H>(positions, Some(proof.composition_root), 1, depth, options.transcript, options.fri_folding_factor)?;
+
+        positions.iter().enumerate().map(|(i, p)| {
+            let x = E::from(g2.pow(*p as u64));
+            let (inv_x_minus_z, inv_x_minus_g_z) = ((x - z).inv(), (x - g_z).inv());
+
+            let h_of_x: E = read_element(&compositions[i], 0, 1, *p)?;
+            let mut d = deep_coefficients[2 * width] * (h_of_x - proof.ood_composition) * inv_x_minus_z;
+
+            for r in 0..width {
+                let p_of_x = E::from(read_element::<A::BaseField>(&rows[i], r * A::BaseField::ELEMENT_BYTES, 0, *p)?);
+                d += deep_coefficients[2 * r] * (p_of_x - current[r]) * inv_x_minus_z + deep_coefficients[2 * r + 1] * (p_of_x - next[r]) * inv_x_minus_g_z;
+            }
+
+            Ok(d)
+        }).collect()
+    };
+
+    verify_low_degree_proof::<A::BaseField, E, H>(&mut transcript, FirstLayer::Computed(&deep_values), g2, &proof.fri_proof, context.degree_bound, None, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::air::{Assertion, ExprAir, Expr, FibonacciAir, MimcAir, MimcPublicInputs};
+    use crate::field::{Goldilocks, CubicExtension};
+    use crate::prover::prove_deep;
+    use crate::utils::mimc;
+
+    type E = CubicExtension<Goldilocks>;
+
+    fn options() -> ProofOptions {
+        ProofOptions::new(8, 40, 20, 4, HashFunction::Blake2s).unwrap()
+    }
+
+    fn mimc_air(output: Goldilocks) -> MimcAir<Goldilocks> {
+        let constants: Vec<Goldilocks> = (0..16u64).map(|i| Goldilocks::from(i.pow(7) ^ 42)).collect();
+        MimcAir::new(MimcPublicInputs::new(Goldilocks::from(5u64), 512, &constants, output))
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        let mut air = mimc_air(Goldilocks::ZERO);
+        air.inputs.output = mimc(air.inputs.inp, 512, &air.inputs.round_constants);
+
+        let proof: DeepProof<E> = prove_deep(&air, &[air.trace()], &options()).unwrap();
+        assert_eq!(verify_deep(&air, &proof, &options()), Ok(()));
+
+        // two registers
+        let trace = FibonacciAir::new(256, Goldilocks::ZERO).trace();
+        let fibonacci = FibonacciAir::new(256, trace[1][255]);
+        let proof: DeepProof<E> = prove_deep(&fibonacci, &trace, &options()).unwrap();
+        assert_eq!(verify_deep(&fibonacci, &proof, &options()), Ok(()));
+
+        // periodic assertions: a counter that restarts every 8 steps
+        let counter: Vec<Goldilocks> = (0..256u64).map(|i| Goldilocks::from(i % 8)).collect();
+        let mut expr_air = ExprAir::new(256, 1, &[]);
+        let reset = expr_air.add_periodic_column(&[Goldilocks::ONE, Goldilocks::ONE, Goldilocks::ONE, Goldilocks::ONE, Goldilocks::ONE, Goldilocks::ONE, Goldilocks::ONE, Goldilocks::ZERO]);
+        expr_air.add_transition(Expr::next(0) - (Expr::current(0) + Expr::constant(Goldilocks::ONE)) * reset).unwrap();
+        expr_air.add_assertion(Assertion::periodic(0, 0, 8, Goldilocks::ZERO));
+        expr_air.add_assertion(Assertion::single(0, 255, Goldilocks::from(7u64)));
+
+        let proof: DeepProof<E> = prove_deep(&expr_air, &[counter], &options()).unwrap();
+        assert_eq!(verify_deep(&expr_air, &proof, &options()), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_proofs_are_rejected() {
+        let mut air = mimc_air(Goldilocks::ZERO);
+        let output = mimc(air.inputs.inp, 512, &air.inputs.round_constants);
+        air.inputs.output = output;
+
+        let mut proof: DeepProof<E> = prove_deep(&air, &[air.trace()], &options()).unwrap();
+
+        assert!(verify_deep(&mimc_air(output + Goldilocks::ONE), &proof, &options()).is_err());
+
+        let legacy = ProofOptions { transcript: TranscriptMode::Legacy, ..options() };
+        assert_eq!(verify_deep(&air, &proof, &legacy), Err(VerifyError::BadParameters("DEEP proofs need the labeled transcript")));
+
+        // the composition at z is bound to the constraints, the frame to the committed trace
+        proof.ood_composition += E::ONE;
+        assert_eq!(verify_deep(&air, &proof, &options()), Err(VerifyError::OutOfDomainConstraint));
+        proof.ood_composition -= E::ONE;
+
+        proof.ood_frame[1] += E::ONE;
+        assert!(verify_deep(&air, &proof, &options()).is_err());
+        proof.ood_frame.pop();
+        assert_eq!(verify_deep(&air, &proof, &options()), Err(VerifyError::OutOfDomainFrame { expected: 2, actual: 1 }));
+
+        // a trace that breaks a transition gives a composition that isn't of low degree
+        let mut trace = air.trace();
+        trace[100] += Goldilocks::ONE;
+        let proof: DeepProof<E> = prove_deep(&air, &[trace], &options()).unwrap();
+        assert!(verify_deep(&air, &proof, &options()).is_err());
+    }
+}
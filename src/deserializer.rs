@@ -1,144 +1,258 @@
 use crate::merkle_tree::*;
 use crate::proof::*;
 use crate::utils::*;
+use crate::error::DeserializeError;
+use crate::field::Field;
 
 // FRI proof element types
-const MERKLE: u32 = 1;
-const POINTS: u32 = 2;
+pub(crate) const MERKLE: u32 = 1;
+pub(crate) const POINTS: u32 = 2;
 
-fn read_exact(source: &[u8], destination: &mut [u8], offset: u32) -> Result<u32, &'static str> {
-    println!("offset is {}", offset);
+fn remaining(source: &[u8], offset: u32, len: usize) -> Result<(), DeserializeError> {
+    let available = source.len().saturating_sub(offset as usize);
+
+    if len > available {
+        return Err(DeserializeError::UnexpectedEof { offset, needed: (len - available) as u32 });
+    }
+
+    if (offset as u64) + (len as u64) > u32::MAX as u64 {
+        return Err(DeserializeError::LengthOverflow { offset });
+    }
+
+    Ok(())
+}
+
+pub(crate) fn read_exact(source: &[u8], destination: &mut [u8], offset: u32) -> Result<u32, DeserializeError> {
     let len = destination.len();
+    remaining(source, offset, len)?;
     destination[..].clone_from_slice(&source[(offset as usize)..(offset as usize + len)]);
     Ok(len as u32)
 }
 
-fn _stark_proof_from_bytes(bytes: &[u8], start_offset: u32) -> Result<(StarkProof, u32), &'static str> {
+// like read_exact for sizes taken from the input: the bounds are checked before anything is allocated
+fn read_vec(source: &[u8], len: u32, offset: u32) -> Result<Vec<u8>, DeserializeError> {
+    remaining(source, offset, len as usize)?;
+    Ok(source[(offset as usize)..(offset as usize + len as usize)].to_vec())
+}
+
+pub(crate) fn read_u32(source: &[u8], offset: u32) -> Result<u32, DeserializeError> {
+    let mut bytes = [0u8; 4];
+    read_exact(source, &mut bytes[..], offset)?;
+    Ok(as_u32_le(&bytes))
+}
+
+pub(crate) fn _stark_proof_from_bytes<F: Field>(bytes: &[u8], start_offset: u32) -> Result<(StarkProof<F>, u32), DeserializeError> {
     let mut offset = start_offset;
     let mut merkle_root = [0u8; 32];
     let mut l_merkle_root = [0u8; 32];
 
-    println!("intput len is {}", bytes.len());
-    offset += read_exact(bytes, &mut merkle_root[..], offset).unwrap();
-    offset += read_exact(bytes, &mut l_merkle_root[..], offset).unwrap();
+    offset += read_exact(bytes, &mut merkle_root[..], offset)?;
+    offset += read_exact(bytes, &mut l_merkle_root[..], offset)?;
 
     let mut ldp_merkle_proofs: Vec<LDPMerkleProof> = Vec::new();
-    let mut points_proof: LDPPointsProof = Default::default();
+    let mut points_proof: LDPPointsProof<F> = Default::default();
     let mut done = false;
     while !done {
         // read the type
-        let mut proof_element_type_bytes = [0u8; 4];
-        offset += read_exact(bytes, &mut proof_element_type_bytes[..], offset).unwrap();
-
-        let proof_element_type = as_u32_le(&proof_element_type_bytes);
+        let proof_element_type = read_u32(bytes, offset)?;
 
         if proof_element_type == MERKLE {
+            offset += 4;
             let mut m: LDPMerkleProof = Default::default();
-            offset += read_exact(bytes, &mut m.root2[..], offset).unwrap();
+            offset += read_exact(bytes, &mut m.root2[..], offset)?;
 
-            let (column_branches, column_branches_size) = bytes_to_multiproof(bytes, offset).expect("column branches deserialization");
+            let (column_branches, column_branches_size) = bytes_to_multiproof(bytes, offset)?;
             m.column_branches = column_branches;
             offset += column_branches_size;
-            m.column_branches.root = m.root2.clone(); // TODO replace this by having branches reference the same merkle root instead of copying it for each proof
-            let (poly_branches, poly_branches_size) =  bytes_to_multiproof(bytes, offset).expect("poly branches deserialization");
+            m.column_branches.root = m.root2; // TODO replace this by having branches reference the same merkle root instead of copying it for each proof
+            let (poly_branches, poly_branches_size) = bytes_to_multiproof(bytes, offset)?;
             m.poly_branches = poly_branches;
             ldp_merkle_proofs.push(m);
             offset += poly_branches_size;
         } else if proof_element_type == POINTS {
+            offset += 4;
             // points are the direct component of the stark proof
-            let mut points_size_bytes = [0u8; 4];
-            offset += read_exact(bytes, &mut points_size_bytes[..], offset).unwrap();
+            let points_size = read_u32(bytes, offset)?;
 
-            let points_size = as_u32_le(&points_size_bytes);
-            assert!(points_size > 0, "more than zero points required");
-            assert!(points_size % 32 == 0, "points size not divisible by 32");
+            if points_size == 0 || points_size % F::ELEMENT_BYTES as u32 != 0 {
+                return Err(DeserializeError::MisalignedPoints { offset, size: points_size });
+            }
 
-            let mut points_bytes = vec![0u8; points_size as usize];
-            offset += read_exact(bytes, &mut points_bytes[..], offset).unwrap();
+            offset += 4;
+
+            let points_bytes = read_vec(bytes, points_size, offset)?;
+
+            for point in points_bytes.chunks(F::ELEMENT_BYTES) {
+                points_proof.push(F::from_bytes_be(point).ok_or(DeserializeError::NonCanonicalElement { offset })?);
+                offset += F::ELEMENT_BYTES as u32;
+            }
 
             done = true;
         } else {
-            panic!("invalid proof element type");
+            return Err(DeserializeError::UnknownElementType { offset, element_type: proof_element_type });
         }
     }
 
     let fri_proof = FRIProof {
         merkle_proofs: ldp_merkle_proofs,
-        points_proof: points_proof,
+        points_proof,
     };
 
-    let (merkle_branches, mut size) = bytes_to_multiproof(bytes, offset).expect("main merkle branches");
-    offset += size as u32;
+    let (merkle_branches, size) = bytes_to_multiproof(bytes, offset)?;
+    offset += size;
 
-    let (linear_comb_branches, size) = bytes_to_multiproof(bytes, offset).expect("linear combination branches");
-    offset += size as u32;
+    let (linear_comb_branches, size) = bytes_to_multiproof(bytes, offset)?;
+    offset += size;
 
-    let mut proof = StarkProof {
-        merkle_root, 
+    let proof = StarkProof {
+        merkle_root,
         l_merkle_root,
-        merkle_branches: merkle_branches,
-        linear_comb_branches: linear_comb_branches,
-        fri_proof: fri_proof,
+        merkle_branches,
+        linear_comb_branches,
+        fri_proof,
     };
 
     Ok((proof, offset))
 }
 
-fn bytes_to_multiproof(bytes: &[u8], start_offset: u32) -> Result<(MultiProof, u32), &'static str> {
-    let mut branches: MultiProof = Default::default();
-    let mut num_branches_bytes = [0u8; 4];
-    let mut num_branches: u32 = 0;
+fn bytes_to_multiproof(bytes: &[u8], start_offset: u32) -> Result<(MultiProof, u32), DeserializeError> {
     let mut offset = start_offset;
 
-    offset += read_exact(bytes, &mut num_branches_bytes[..], offset).unwrap();
-    num_branches = as_u32_le(&num_branches_bytes);
+    let num_branches = read_u32(bytes, offset)?;
+    offset += 4;
 
     let mut branches: Vec<ProofBranch> = Default::default();
 
-    for branch in 0..(num_branches as usize) {
-        let mut witnesses_size_bytes = [0u8; 4];
-        let mut value_size_bytes = [0u8; 4];
-
+    for _ in 0..num_branches {
         let mut witnesses: Vec<MerkleDigest> = Default::default();
 
-        offset += read_exact(bytes, &mut value_size_bytes[..], offset).unwrap();
+        let value_size = read_u32(bytes, offset)?;
+        offset += 4;
 
-        let value_size = as_u32_le(&mut value_size_bytes);
+        let value: Value = read_vec(bytes, value_size, offset)?;
+        offset += value_size;
+        let sibling_value: Value = read_vec(bytes, value_size, offset)?;
+        offset += value_size;
 
-        let mut value: Value = vec![0u8; value_size as usize];
-        let mut sibling_value: Value = vec![0u8; value_size as usize];
+        let witnesses_size = read_u32(bytes, offset)?;
 
-        offset += read_exact(bytes, &mut value[0..value_size as usize], offset).unwrap();
-        offset += read_exact(bytes, &mut sibling_value[0..value_size as usize], offset).unwrap();
-
-        offset += read_exact(bytes, &mut witnesses_size_bytes[..], offset).unwrap();
+        if witnesses_size % 32 != 0 {
+            return Err(DeserializeError::MisalignedWitnesses { offset, size: witnesses_size });
+        }
 
-        let witnesses_size = as_u32_le(&mut witnesses_size_bytes);
-        assert!(witnesses_size % 32 == 0, format!("witnesses should all be 32 bytes: {}", witnesses_size));
+        offset += 4;
+        remaining(bytes, offset, witnesses_size as usize)?;
 
         let num_witnesses = witnesses_size / 32;
 
-        for i in 0..(num_witnesses as usize) {
+        for _ in 0..num_witnesses {
             let mut witness = [0u8; 32];
-            offset += read_exact(bytes, &mut witness[..], offset).unwrap();
+            offset += read_exact(bytes, &mut witness[..], offset)?;
             witnesses.push(witness);
         }
 
         branches.push(ProofBranch {
-            witnesses: witnesses,
-            sibling_value: sibling_value,
-            value: value
+            witnesses,
+            sibling_value,
+            value
         });
     }
 
     let multiproof = MultiProof {
-        branches: branches,
+        branches,
         root: Default::default()
     };
 
     Ok((multiproof, offset - start_offset))
 }
 
-pub fn from_bytes(bytes: &[u8]) -> Result<(StarkProof, u32), &'static str> {
-    _stark_proof_from_bytes(bytes, 0)
+/// Parses a complete serialized proof over the field `F`. Any bytes left after the proof are an error.
+pub fn from_bytes<F: Field>(bytes: &[u8]) -> Result<StarkProof<F>, DeserializeError> {
+    let (proof, offset) = _stark_proof_from_bytes(bytes, 0)?;
+
+    if (offset as usize) != bytes.len() {
+        return Err(DeserializeError::TrailingBytes { offset });
+    }
+
+    Ok(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldElement;
+
+    fn proof_bytes() -> Vec<u8> {
+        std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/proof.bin")).unwrap()
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let proof = from_bytes::<FieldElement>(&proof_bytes()).unwrap();
+
+        assert!(!proof.fri_proof.merkle_proofs.is_empty());
+        assert_eq!(proof.fri_proof.points_proof.len(), 64);
+        assert_eq!(proof.linear_comb_branches.branches.len() * 2, proof.merkle_branches.branches.len());
+    }
+
+    #[test]
+    fn test_truncated_input() {
+        let bytes = proof_bytes();
+
+        for len in (0..bytes.len()).step_by(997) {
+            match from_bytes::<FieldElement>(&bytes[..len]) {
+                Err(DeserializeError::UnexpectedEof { .. }) => {},
+                res => panic!("unexpected result for {} bytes: {:?}", len, res.err()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_trailing_bytes() {
+        let mut bytes = proof_bytes();
+        let len = bytes.len() as u32;
+        bytes.push(0);
+
+        assert_eq!(from_bytes::<FieldElement>(&bytes).err(), Some(DeserializeError::TrailingBytes { offset: len }));
+    }
+
+    #[test]
+    fn test_unknown_element_type() {
+        let mut bytes = proof_bytes();
+        bytes[64] = 7;
+
+        assert_eq!(from_bytes::<FieldElement>(&bytes).err(), Some(DeserializeError::UnknownElementType { offset: 64, element_type: 7 }));
+    }
+
+    #[test]
+    fn test_non_canonical_point() {
+        let mut bytes = proof_bytes();
+        let proof = from_bytes::<FieldElement>(&bytes).unwrap();
+        let points_offset = bytes.len() - 4 - 32 * proof.fri_proof.points_proof.len()
+            - multiproof_size(&proof.merkle_branches) - multiproof_size(&proof.linear_comb_branches);
+
+        for b in &mut bytes[(points_offset + 4)..(points_offset + 36)] {
+            *b = 0xff;
+        }
+
+        assert_eq!(from_bytes::<FieldElement>(&bytes).err(), Some(DeserializeError::NonCanonicalElement { offset: points_offset as u32 + 4 }));
+    }
+
+    fn multiproof_size(proof: &MultiProof) -> usize {
+        4 + proof.branches.iter().map(|b| 8 + 2 * b.value.len() + 32 * b.witnesses.len()).sum::<usize>()
+    }
+
+    #[test]
+    fn test_huge_lengths() {
+        // roots, a merkle element and a column multiproof with one branch claiming a 4GB value
+        let mut bytes = vec![0u8; 100];
+        bytes[64] = MERKLE as u8;
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+
+        match from_bytes::<FieldElement>(&bytes) {
+            Err(DeserializeError::UnexpectedEof { offset: 108, .. }) => {},
+            res => panic!("unexpected result: {:?}", res.err()),
+        }
+    }
 }
@@ -0,0 +1,129 @@
+use std::fmt;
+
+/// Reasons a STARK proof can be rejected by the verifier.
+///
+/// Merkle related variants carry a `layer` identifying the commitment that failed:
+/// 0 is the trace commitment, 1 the linear combination commitment and `2 + i` the
+/// column commitment of FRI layer `i`. In DEEP proofs 1 is the constraint composition
+/// commitment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// a Merkle branch did not hash up to the committed root
+    MerkleRootMismatch { layer: usize, index: u32 },
+    /// the proof contains a different number of branches than positions were queried
+    BranchCountMismatch { layer: usize, expected: usize, actual: usize },
+    /// a committed leaf is too short to hold the values the verifier expects
+    MalformedLeaf { layer: usize, index: u32 },
+    /// C(P(x)) != Z(x) * D(x) at a spot-checked position
+    TransitionConstraint { position: u32 },
+    /// B(x) * Q(x) + I(x) != P(x) at a spot-checked position
+    BoundaryConstraint { position: u32 },
+    /// the committed linear combination doesn't match the trace values
+    LinearCombination { position: u32 },
+    /// a column value is not on the degree < 4 polynomial through its row
+    LowDegree { layer: usize, query: usize },
+    /// the FRI proof has more layers than the evaluation domain allows
+    TooManyFriLayers,
+    /// the FRI proof stops before the degree bound is small enough to check directly
+    TooFewFriLayers,
+    /// the final FRI layer doesn't have one point per element of the remaining domain
+    FinalLayerSize { expected: usize, actual: usize },
+    /// the points of the final FRI layer aren't the ones committed by the last layer root
+    FinalLayerRootMismatch,
+    /// the points of the final FRI layer exceed the degree bound
+    FinalLayerDegree,
+    /// too few points of the final FRI layer are left to interpolate once the excluded
+    /// positions are taken out
+    FinalLayerTooFewPoints { needed: usize, available: usize },
+    /// the public parameters can't describe a valid proof
+    BadParameters(&'static str),
+    /// the public input digest in the proof header is for a different statement
+    PublicInputMismatch,
+    /// a proof container without a public input digest, which only legacy proofs may omit
+    MissingPublicInputDigest,
+    /// the options in the proof header aren't the ones the verifier expects
+    OptionsMismatch,
+    /// a DEEP proof doesn't have the registers at z and g * z
+    OutOfDomainFrame { expected: usize, actual: usize },
+    /// the constraint composition at z doesn't match the constraints at the registers there
+    OutOfDomainConstraint,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerifyError::MerkleRootMismatch { layer, index } => write!(f, "merkle branch for index {} doesn't match the root of layer {}", index, layer),
+            VerifyError::BranchCountMismatch { layer, expected, actual } => write!(f, "expected {} merkle branches in layer {}, found {}", expected, layer, actual),
+            VerifyError::MalformedLeaf { layer, index } => write!(f, "leaf {} of layer {} is malformed", index, layer),
+            VerifyError::TransitionConstraint { position } => write!(f, "transition constraint check failed at position {}", position),
+            VerifyError::BoundaryConstraint { position } => write!(f, "boundary constraint check failed at position {}", position),
+            VerifyError::LinearCombination { position } => write!(f, "invalid linear combination at position {}", position),
+            VerifyError::LowDegree { layer, query } => write!(f, "low degree test failed in layer {} for query {}", layer, query),
+            VerifyError::TooManyFriLayers => write!(f, "too many FRI layers for the evaluation domain"),
+            VerifyError::TooFewFriLayers => write!(f, "too few FRI layers to check the final layer directly"),
+            VerifyError::FinalLayerSize { expected, actual } => write!(f, "expected {} points in the final FRI layer, found {}", expected, actual),
+            VerifyError::FinalLayerRootMismatch => write!(f, "final FRI layer points don't match the committed root"),
+            VerifyError::FinalLayerDegree => write!(f, "final FRI layer exceeds the degree bound"),
+            VerifyError::FinalLayerTooFewPoints { needed, available } => write!(f, "need {} points of the final FRI layer to check its degree, only {} are left", needed, available),
+            VerifyError::BadParameters(reason) => write!(f, "bad parameters: {}", reason),
+            VerifyError::PublicInputMismatch => write!(f, "the proof is for different public inputs"),
+            VerifyError::MissingPublicInputDigest => write!(f, "the proof container has no public input digest"),
+            VerifyError::OptionsMismatch => write!(f, "the proof was generated with different options"),
+            VerifyError::OutOfDomainFrame { expected, actual } => write!(f, "expected {} out-of-domain trace values, found {}", expected, actual),
+            VerifyError::OutOfDomainConstraint => write!(f, "constraint check failed at the out-of-domain point"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Reasons a serialized proof can fail to parse. Every variant records the byte
+/// offset at which parsing stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeserializeError {
+    /// the input ended while `needed` more bytes were expected at `offset`
+    UnexpectedEof { offset: u32, needed: u32 },
+    /// a FRI proof element with an unknown type tag
+    UnknownElementType { offset: u32, element_type: u32 },
+    /// a witness section whose size isn't a multiple of the digest size
+    MisalignedWitnesses { offset: u32, size: u32 },
+    /// a points section that is empty or whose size isn't a multiple of the element size
+    MisalignedPoints { offset: u32, size: u32 },
+    /// a field element encoding that isn't less than the modulus
+    NonCanonicalElement { offset: u32 },
+    /// the proof was parsed completely but input remains
+    TrailingBytes { offset: u32 },
+    /// a length field points past the largest addressable offset
+    LengthOverflow { offset: u32 },
+    /// a proof container that doesn't start with the magic bytes
+    BadMagic,
+    /// a proof container in a format version this verifier doesn't know
+    UnsupportedVersion { version: u32 },
+    /// the proof is over a different field than the one it is parsed as
+    FieldMismatch { expected: u32, actual: u32 },
+    /// a header field naming a hash function, transcript mode, ... that doesn't exist
+    UnknownIdentifier { offset: u32, id: u32 },
+    /// the options in the header aren't the ones the verifier expects
+    OptionsMismatch,
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeserializeError::UnexpectedEof { offset, needed } => write!(f, "unexpected end of input at offset {}, {} more bytes needed", offset, needed),
+            DeserializeError::UnknownElementType { offset, element_type } => write!(f, "unknown FRI proof element type {} at offset {}", element_type, offset),
+            DeserializeError::MisalignedWitnesses { offset, size } => write!(f, "witnesses size {} at offset {} isn't a multiple of 32", size, offset),
+            DeserializeError::MisalignedPoints { offset, size } => write!(f, "points size {} at offset {} isn't a positive multiple of 32", size, offset),
+            DeserializeError::NonCanonicalElement { offset } => write!(f, "non canonical field element at offset {}", offset),
+            DeserializeError::TrailingBytes { offset } => write!(f, "trailing bytes after the proof at offset {}", offset),
+            DeserializeError::LengthOverflow { offset } => write!(f, "length at offset {} overflows", offset),
+            DeserializeError::BadMagic => write!(f, "not a proof container"),
+            DeserializeError::UnsupportedVersion { version } => write!(f, "unsupported proof container version {}", version),
+            DeserializeError::FieldMismatch { expected, actual } => write!(f, "expected a proof over field {:#x}, found {:#x}", expected, actual),
+            DeserializeError::UnknownIdentifier { offset, id } => write!(f, "unknown identifier {} at offset {}", id, offset),
+            DeserializeError::OptionsMismatch => write!(f, "the proof was generated with different options"),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
@@ -1,86 +1,251 @@
-use num_bigint::BigInt;
-use crate::utils::{negative_to_positive};
-
-// do (a-b)%modulus where a may be greater than b
-// inspiration: https://internals.rust-lang.org/t/mathematical-modulo-operator/5952
-fn submod(a: &BigInt, b: &BigInt, modulus: &BigInt) -> BigInt {
-    match b > a {
-        true => {
-            let res = b - a;
-            negative_to_positive(&res, modulus)
-        },
-        false => {
-            (a-b) % modulus
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use lazy_static::lazy_static;
+
+use crate::field::Field;
+use crate::utils::is_power_of_2;
+
+// a Vec<F> of twiddles for some field F, keyed by the field and the serialized root
+type TwiddleCache = HashMap<(TypeId, Vec<u8>), Arc<dyn Any + Send + Sync>>;
+
+lazy_static! {
+    // twiddle tables shared by every transform over the same domain
+    static ref TWIDDLES: Mutex<TwiddleCache> = Mutex::new(HashMap::new());
+}
+
+// 1, root, root^2, ... root^(n/2 - 1), where root has to have order n
+fn compute_twiddles<F: Field>(root_of_unity: &F, n: usize) -> Vec<F> {
+    let mut powers: Vec<F> = vec![F::ONE];
+
+    for _ in 1..(n / 2) {
+        let next = powers[powers.len()-1] * *root_of_unity;
+        powers.push(next);
+    }
+
+    // root^(n/2) has to be -1 for the butterflies, which also pins down the order
+    let half = powers[powers.len()-1] * *root_of_unity;
+    assert!(if n == 1 { *root_of_unity == F::ONE } else { half == -F::ONE }, "root of unity doesn't have order {}", n);
+
+    powers
+}
+
+fn twiddles<F: Field>(root_of_unity: &F, n: usize) -> Arc<Vec<F>> {
+    let key = (TypeId::of::<F>(), root_of_unity.to_bytes_be());
+
+    if let Some(table) = TWIDDLES.lock().unwrap().get(&key) {
+        if let Ok(table) = table.clone().downcast::<Vec<F>>() {
+            if table.len() == (n / 2).max(1) {
+                return table;
+            }
         }
     }
+
+    // computed outside of the lock, a race only means the table is built twice
+    let table = Arc::new(compute_twiddles(root_of_unity, n));
+    TWIDDLES.lock().unwrap().insert(key, table.clone());
+
+    table
 }
 
-fn simple_ft(vals: &Vec<BigInt>, roots_of_unity: &Vec<BigInt>, modulus: &BigInt) -> Vec<BigInt> {
-    if vals.len() > 4 {
-        panic!("called ft with more than four arguments");
+fn bit_reverse_permute<F: Field>(v: &mut [F]) {
+    let n = v.len();
+    let log_n = n.trailing_zeros();
+
+    if n <= 2 {
+        return;
     }
 
-    let mut output: Vec<BigInt> = Vec::new();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - log_n);
 
-    for i in 0..roots_of_unity.len() {
-        let mut last = BigInt::from(0u8);
-        for j in 0..roots_of_unity.len() {
-            last += vals[j].clone() * &roots_of_unity[(i*j) % roots_of_unity.len()];
+        if i < j {
+            v.swap(i, j);
         }
+    }
+}
+
+// iterative decimation in time: after the bit reversal every block of m values holds a
+// transform of size m, blocks are merged four at a time (two radix-2 levels in one pass)
+fn ntt<F: Field>(v: &mut [F], twiddles: &[F]) {
+    let n = v.len();
+    let mut m = 1;
+
+    bit_reverse_permute(v);
 
-        output.push(last % modulus);
+    // an odd number of levels leaves a single radix-2 level, done first
+    if n.trailing_zeros() % 2 == 1 {
+        for pair in v.chunks_mut(2) {
+            let (u, t) = (pair[0], pair[1]);
+            pair[0] = u + t;
+            pair[1] = u - t;
+        }
+
+        m = 2;
     }
 
-    output
-}
+    while m < n {
+        let len = 4 * m;
+        let step1 = n / (2 * m);
+        let step2 = n / len;
+        // root^(n/4), multiplies the twiddles of the upper half of the second level
+        let quarter = twiddles[n / 4];
+
+        for block in v.chunks_mut(len) {
+            for j in 0..m {
+                let w1 = twiddles[j * step1];
+                let w2 = twiddles[j * step2];
+
+                let a = block[j];
+                let b = block[j + m] * w1;
+                let c = block[j + 2 * m];
+                let d = block[j + 3 * m] * w1;
+
+                let (a0, a1) = (a + b, a - b);
+                let c0 = (c + d) * w2;
+                let c1 = (c - d) * w2 * quarter;
+
+                block[j] = a0 + c0;
+                block[j + 2 * m] = a0 - c0;
+                block[j + m] = a1 + c1;
+                block[j + 3 * m] = a1 - c1;
+            }
+        }
 
-fn _fft(v: &Vec<BigInt>, roots: &Vec<BigInt>, modulus: &BigInt) -> Vec<BigInt> {
-    if v.len() <= 4 {
-        return simple_ft(v, roots, &modulus);
+        m = len;
     }
+}
 
-    let right_vals: Vec<BigInt> = v.iter().enumerate().filter(|&(i, _)| i % 2 != 0).map(|(_, e)| e.clone()).collect();
-    let left_vals: Vec<BigInt>  = v.iter().enumerate().filter(|&(i, _)| i % 2 == 0).map(|(_, e)| e.clone()).collect();
-    let new_roots: Vec<BigInt> = roots.iter().enumerate().filter(|&(i, _)| i % 2 == 0).map(|(_, e)| e.clone()).collect();
+// multiplies the i-th value by shift^i
+fn scale<F: Field>(v: &mut [F], shift: &F) {
+    let mut factor = F::ONE;
 
-    let left = _fft(&left_vals, &new_roots, &modulus);
-    let right = _fft(&right_vals, &new_roots, &modulus); 
+    for x in v.iter_mut() {
+        *x *= factor;
+        factor *= *shift;
+    }
+}
 
-    let mut output: Vec<BigInt> = vec![BigInt::from(0u32); v.len()];
+/// In place version of `fft`.
+pub fn fft_in_place<F: Field>(v: &mut [F], root_of_unity: &F) {
+    assert!(is_power_of_2(v.len()), "the number of values has to be a power of 2");
 
-    // TODO why does y not need to be dereferenced here?
-    for (i, (x, y)) in left.iter().zip(right).enumerate() {
-        let y_times_root: BigInt = y * &roots[i];
+    let twiddles = twiddles(root_of_unity, v.len());
+    ntt(v, &twiddles);
+}
 
-        output[i] = negative_to_positive(&(x+&y_times_root.clone()), modulus);
+/// In place version of `fft_inv`.
+pub fn fft_inv_in_place<F: Field>(v: &mut [F], root_of_unity: &F) {
+    fft_in_place(v, &root_of_unity.inv());
 
-        output[i+left.len()] = negative_to_positive(&(x - &y_times_root), &modulus);
+    let invlen = F::from(v.len() as u64).inv();
+    for x in v.iter_mut() {
+        *x *= invlen;
     }
+}
+
+/// Evaluates the polynomial with coefficients `v` at 1, root, root^2, ... where the order
+/// of `root_of_unity` has to be v.len(), a power of 2.
+pub fn fft<F: Field>(v: &[F], root_of_unity: &F) -> Vec<F> {
+    let mut output = v.to_vec();
+    fft_in_place(&mut output, root_of_unity);
 
     output
 }
 
-// inverse fast fourier transform
-pub fn fft_inv(v: &Vec<BigInt>, root_of_unity: &BigInt, modulus: &BigInt) -> Vec<BigInt> {
-    let mut roots_of_unity: Vec<BigInt>  = vec![BigInt::from(1u32), root_of_unity.clone()];
-    let mut vals = v.clone();
-    let one = BigInt::from(1u32);
+/// Interpolates the coefficients of the polynomial taking the values `v` at 1, root,
+/// root^2, ... the inverse of `fft`.
+pub fn fft_inv<F: Field>(v: &[F], root_of_unity: &F) -> Vec<F> {
+    let mut output = v.to_vec();
+    fft_inv_in_place(&mut output, root_of_unity);
 
-    while roots_of_unity[roots_of_unity.len()-1] != one {
-        let new_root = (roots_of_unity[roots_of_unity.len()-1].clone() * root_of_unity.clone()) % modulus;
-        roots_of_unity.push(new_root);
+    output
+}
+
+/// Like `fft`, but evaluates over the coset shift, shift * root, shift * root^2, ...
+pub fn coset_fft<F: Field>(v: &[F], root_of_unity: &F, shift: &F) -> Vec<F> {
+    let mut coeffs = v.to_vec();
+    scale(&mut coeffs, shift);
+    fft_in_place(&mut coeffs, root_of_unity);
+
+    coeffs
+}
+
+/// Interpolates from values over the coset shift, shift * root, ... the inverse of `coset_fft`.
+pub fn coset_ifft<F: Field>(v: &[F], root_of_unity: &F, shift: &F) -> Vec<F> {
+    let mut coeffs = fft_inv(v, root_of_unity);
+    scale(&mut coeffs, &shift.inv());
+
+    coeffs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{FieldElement, Goldilocks};
+    use crate::field::tests::elements;
+    use crate::utils::eval_poly_at;
+
+    fn check_round_trips<F: Field>() {
+        let shift = F::from(7);
+
+        for log_n in 0..11 {
+            let n = 1 << log_n;
+            let root = F::get_root_of_unity(n as u64);
+            let mut coeffs: Vec<F> = elements(n);
+            coeffs.truncate(n);
+
+            let evals = fft(&coeffs, &root);
+            assert_eq!(fft_inv(&evals, &root), coeffs);
+            assert_eq!(coset_ifft(&coset_fft(&coeffs, &root, &shift), &root, &shift), coeffs);
+        }
+    }
+
+    #[test]
+    fn test_round_trips() {
+        check_round_trips::<FieldElement>();
+        check_round_trips::<Goldilocks>();
     }
 
-    if roots_of_unity.len() > vals.len() {
-        // TODO optimize this so that no array copying is done
-        roots_of_unity.append(&mut vec![BigInt::from(0u32); roots_of_unity.len() - vals.len() - 1]);
+    #[test]
+    fn test_fft_evaluates() {
+        let shift = Goldilocks::from(7);
+
+        // odd and even numbers of levels take different paths
+        for n in [32usize, 64].iter() {
+            let coeffs: Vec<Goldilocks> = elements(*n);
+            let root = Goldilocks::get_root_of_unity(*n as u64);
+
+            let evals = fft(&coeffs, &root);
+            let coset_evals = coset_fft(&coeffs, &root, &shift);
+
+            for i in 0..*n {
+                let x = root.pow(i as u64);
+                assert_eq!(evals[i], eval_poly_at(&coeffs, x));
+                assert_eq!(coset_evals[i], eval_poly_at(&coeffs, shift * x));
+            }
+        }
     }
 
-    roots_of_unity.reverse();
-    roots_of_unity.remove(roots_of_unity.len()-1);
+    #[test]
+    fn test_large_domain() {
+        let n = 1 << 20;
+        let root = Goldilocks::get_root_of_unity(n as u64);
+        let mut values: Vec<Goldilocks> = (0..n as u64).map(Goldilocks::from).collect();
+
+        fft_in_place(&mut values, &root);
+        assert_eq!(values[0], Goldilocks::from((n as u64) * (n as u64 - 1) / 2));
+        assert_eq!(values[1], eval_poly_at(&(0..n as u64).map(Goldilocks::from).collect::<Vec<_>>(), root));
 
-    let invlen = BigInt::from(vals.len()).modpow(&(modulus-BigInt::from(2u8)), &modulus);
-    let mut result: Vec<BigInt> = _fft(v, &roots_of_unity, modulus);
-    result = result.iter().map(|x| (x.clone() * &invlen) % modulus).collect();
-    result
+        fft_inv_in_place(&mut values, &root);
+        assert!(values.iter().enumerate().all(|(i, x)| *x == Goldilocks::from(i as u64)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_wrong_root_order() {
+        let values: Vec<FieldElement> = elements(16);
+        fft(&values, &FieldElement::get_root_of_unity(32));
+    }
 }
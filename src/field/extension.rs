@@ -0,0 +1,275 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use super::{ExtensibleField, Field};
+
+// each coefficient of a challenge is reduced from its own slice of the random bytes, left
+// padded to the 32 bytes the base field expects
+fn coefficients_from_bytes<B: Field>(bytes: &[u8; 32], n: usize) -> Vec<B> {
+    let chunk = 32 / n;
+
+    (0..n).map(|i| {
+        let mut padded = [0u8; 32];
+        padded[(32 - chunk)..].copy_from_slice(&bytes[(i * chunk)..((i + 1) * chunk)]);
+        B::from_bytes_be_reduced(&padded)
+    }).collect()
+}
+
+/// The quadratic extension B[u] / (u^2 - B::NONRESIDUE). An element a0 + a1 * u is stored
+/// as [a0, a1] and serialized as a0 followed by a1.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct QuadExtension<B: ExtensibleField>(pub [B; 2]);
+
+/// The cubic extension B[u] / (u^3 - B::NONRESIDUE). An element a0 + a1 * u + a2 * u^2 is
+/// stored as [a0, a1, a2] and serialized in that order.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct CubicExtension<B: ExtensibleField>(pub [B; 3]);
+
+impl<B: ExtensibleField> Field for QuadExtension<B> {
+    const ZERO: Self = QuadExtension([B::ZERO, B::ZERO]);
+    const ONE: Self = QuadExtension([B::ONE, B::ZERO]);
+
+    const ELEMENT_BYTES: usize = 2 * B::ELEMENT_BYTES;
+
+    // the evaluation domain stays in the base field
+    const TWO_ADICITY: u32 = B::TWO_ADICITY;
+
+    const FIELD_ID: u32 = 0x0200 | (B::FIELD_ID & 0xff);
+
+    // (a0 + a1 * u) * (a0 - a1 * u) = a0^2 - w * a1^2 is in the base field
+    fn inv(&self) -> Self {
+        let [a0, a1] = self.0;
+        let norm_inv = (a0.square() - B::NONRESIDUE * a1.square()).inv();
+
+        QuadExtension([a0 * norm_inv, -a1 * norm_inv])
+    }
+
+    fn get_root_of_unity(order: u64) -> Self {
+        Self::from(B::get_root_of_unity(order))
+    }
+
+    fn from_bytes_be(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::ELEMENT_BYTES {
+            return None;
+        }
+
+        let (a0, a1) = bytes.split_at(B::ELEMENT_BYTES);
+        Some(QuadExtension([B::from_bytes_be(a0)?, B::from_bytes_be(a1)?]))
+    }
+
+    fn to_bytes_be(&self) -> Vec<u8> {
+        [self.0[0].to_bytes_be(), self.0[1].to_bytes_be()].concat()
+    }
+
+    fn from_bytes_be_reduced(bytes: &[u8; 32]) -> Self {
+        let c = coefficients_from_bytes(bytes, 2);
+        QuadExtension([c[0], c[1]])
+    }
+}
+
+impl<B: ExtensibleField> Field for CubicExtension<B> {
+    const ZERO: Self = CubicExtension([B::ZERO, B::ZERO, B::ZERO]);
+    const ONE: Self = CubicExtension([B::ONE, B::ZERO, B::ZERO]);
+
+    const ELEMENT_BYTES: usize = 3 * B::ELEMENT_BYTES;
+
+    // the evaluation domain stays in the base field
+    const TWO_ADICITY: u32 = B::TWO_ADICITY;
+
+    const FIELD_ID: u32 = 0x0300 | (B::FIELD_ID & 0xff);
+
+    // the adjugate of the multiplication-by-a matrix, divided by its determinant (the norm)
+    fn inv(&self) -> Self {
+        let [a0, a1, a2] = self.0;
+        let w = B::NONRESIDUE;
+
+        let c0 = a0.square() - w * a1 * a2;
+        let c1 = w * a2.square() - a0 * a1;
+        let c2 = a1.square() - a0 * a2;
+        let norm_inv = (a0 * c0 + w * (a2 * c1 + a1 * c2)).inv();
+
+        CubicExtension([c0 * norm_inv, c1 * norm_inv, c2 * norm_inv])
+    }
+
+    fn get_root_of_unity(order: u64) -> Self {
+        Self::from(B::get_root_of_unity(order))
+    }
+
+    fn from_bytes_be(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::ELEMENT_BYTES {
+            return None;
+        }
+
+        let mut c = bytes.chunks(B::ELEMENT_BYTES).map(B::from_bytes_be);
+        Some(CubicExtension([c.next()??, c.next()??, c.next()??]))
+    }
+
+    fn to_bytes_be(&self) -> Vec<u8> {
+        self.0.iter().flat_map(|c| c.to_bytes_be()).collect()
+    }
+
+    fn from_bytes_be_reduced(bytes: &[u8; 32]) -> Self {
+        let c = coefficients_from_bytes(bytes, 3);
+        CubicExtension([c[0], c[1], c[2]])
+    }
+}
+
+impl<B: ExtensibleField> From<B> for QuadExtension<B> {
+    fn from(value: B) -> Self {
+        QuadExtension([value, B::ZERO])
+    }
+}
+
+impl<B: ExtensibleField> From<B> for CubicExtension<B> {
+    fn from(value: B) -> Self {
+        CubicExtension([value, B::ZERO, B::ZERO])
+    }
+}
+
+impl<B: ExtensibleField> From<u64> for QuadExtension<B> {
+    fn from(value: u64) -> Self {
+        Self::from(B::from(value))
+    }
+}
+
+impl<B: ExtensibleField> From<u64> for CubicExtension<B> {
+    fn from(value: u64) -> Self {
+        Self::from(B::from(value))
+    }
+}
+
+impl<B: ExtensibleField> Mul for QuadExtension<B> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let [a0, a1] = self.0;
+        let [b0, b1] = rhs.0;
+
+        QuadExtension([a0 * b0 + B::NONRESIDUE * a1 * b1, a0 * b1 + a1 * b0])
+    }
+}
+
+impl<B: ExtensibleField> Mul for CubicExtension<B> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let [a0, a1, a2] = self.0;
+        let [b0, b1, b2] = rhs.0;
+        let w = B::NONRESIDUE;
+
+        CubicExtension([
+            a0 * b0 + w * (a1 * b2 + a2 * b1),
+            a0 * b1 + a1 * b0 + w * a2 * b2,
+            a0 * b2 + a1 * b1 + a2 * b0,
+        ])
+    }
+}
+
+// addition, subtraction and formatting are coefficient wise for both extensions
+macro_rules! impl_coefficient_wise {
+    ($ext:ident, $n:expr) => {
+        impl<B: ExtensibleField> Add for $ext<B> {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                let mut res = self.0;
+                for i in 0..$n {
+                    res[i] += rhs.0[i];
+                }
+                $ext(res)
+            }
+        }
+
+        impl<B: ExtensibleField> Sub for $ext<B> {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                let mut res = self.0;
+                for i in 0..$n {
+                    res[i] -= rhs.0[i];
+                }
+                $ext(res)
+            }
+        }
+
+        impl<B: ExtensibleField> Neg for $ext<B> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self::ZERO - self
+            }
+        }
+
+        impl<B: ExtensibleField> AddAssign for $ext<B> {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl<B: ExtensibleField> SubAssign for $ext<B> {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl<B: ExtensibleField> MulAssign for $ext<B> {
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl<B: ExtensibleField> fmt::Display for $ext<B> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.0[0])?;
+                for i in 1..$n {
+                    write!(f, " + {} * u^{}", self.0[i], i)?;
+                }
+                Ok(())
+            }
+        }
+
+        impl<B: ExtensibleField> fmt::Debug for $ext<B> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self)
+            }
+        }
+    };
+}
+
+impl_coefficient_wise!(QuadExtension, 2);
+impl_coefficient_wise!(CubicExtension, 3);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::Goldilocks;
+    use crate::field::tests::check_field;
+
+    #[test]
+    fn test_field() {
+        check_field::<QuadExtension<Goldilocks>>();
+        check_field::<CubicExtension<Goldilocks>>();
+    }
+
+    #[test]
+    fn test_generator_powers() {
+        // u^2 and u^3 wrap around to the nonresidue
+        let u2 = QuadExtension([Goldilocks::ZERO, Goldilocks::ONE]);
+        let u3 = CubicExtension([Goldilocks::ZERO, Goldilocks::ONE, Goldilocks::ZERO]);
+
+        assert_eq!(u2.square(), QuadExtension::from(Goldilocks::NONRESIDUE));
+        assert_eq!(u3.pow(3), CubicExtension::from(Goldilocks::NONRESIDUE));
+        assert_eq!(u3.square(), CubicExtension([Goldilocks::ZERO, Goldilocks::ZERO, Goldilocks::ONE]));
+    }
+
+    #[test]
+    fn test_challenges_use_every_coefficient() {
+        let bytes = [0x5au8; 32];
+
+        let quad = QuadExtension::<Goldilocks>::from_bytes_be_reduced(&bytes);
+        assert!(quad.0.iter().all(|c| !c.is_zero()));
+
+        let cubic = CubicExtension::<Goldilocks>::from_bytes_be_reduced(&bytes);
+        assert!(cubic.0.iter().all(|c| !c.is_zero()));
+    }
+}
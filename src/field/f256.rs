@@ -0,0 +1,339 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use num_bigint::BigUint;
+
+use super::Field;
+
+// MODULUS = 2^256 - 351 * 2^32 + 1 as little endian limbs
+const P: [u64; 4] = [0xfffffea100000001, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff];
+// P - 2, the exponent of a Fermat inversion
+const P_MINUS_2: [u64; 4] = [0xfffffea0ffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff];
+// 2^256 mod P, the Montgomery form of 1
+const R: [u64; 4] = [0x0000015effffffff, 0, 0, 0];
+// 2^512 mod P, multiplying by it converts into Montgomery form
+const R2: [u64; 4] = [0xfffffd4200000001, 0x000000000001e140, 0, 0];
+// -P^-1 mod 2^64
+const INV: u64 = 0xfffffea0ffffffff;
+// 7^((P - 1) / 2^32) in Montgomery form, generates the largest power of two subgroup
+const TWO_ADIC_ROOT: [u64; 4] = [0x5c4ecb09db7044df, 0xa5372942cb2c195e, 0x878ebe1ed14b1162, 0xb81af332e6c7e14e];
+
+/// An element of the prime field of MODULUS, stored as four u64 limbs in Montgomery
+/// form (a * 2^256 mod MODULUS).
+#[derive(Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct FieldElement([u64; 4]);
+
+#[inline(always)]
+fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let t = (a as u128) + (b as u128) + (carry as u128);
+    (t as u64, (t >> 64) as u64)
+}
+
+#[inline(always)]
+fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let (d, b1) = a.overflowing_sub(b);
+    let (d, b2) = d.overflowing_sub(borrow);
+    (d, (b1 | b2) as u64)
+}
+
+// a + b * c + carry, which can't overflow a u128
+#[inline(always)]
+fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let t = (a as u128) + (b as u128) * (c as u128) + (carry as u128);
+    (t as u64, (t >> 64) as u64)
+}
+
+// subtract P from a value below 2P whose 257th bit is `carry`
+#[inline(always)]
+fn reduce_once(r: [u64; 4], carry: u64) -> [u64; 4] {
+    let mut d = [0u64; 4];
+    let mut borrow = 0;
+
+    for i in 0..4 {
+        let (s, b) = sbb(r[i], P[i], borrow);
+        d[i] = s;
+        borrow = b;
+    }
+
+    if carry != 0 || borrow == 0 {
+        d
+    } else {
+        r
+    }
+}
+
+// CIOS Montgomery multiplication: a * b * 2^-256 mod P
+#[inline(always)]
+fn mont_mul(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut t = [0u64; 6];
+
+    for &bi in b {
+        let mut carry = 0;
+        for j in 0..4 {
+            let (lo, hi) = mac(t[j], a[j], bi, carry);
+            t[j] = lo;
+            carry = hi;
+        }
+        let (s, c) = adc(t[4], carry, 0);
+        t[4] = s;
+        t[5] = c;
+
+        let m = t[0].wrapping_mul(INV);
+        let (_, mut carry) = mac(t[0], m, P[0], 0);
+        for j in 1..4 {
+            let (lo, hi) = mac(t[j], m, P[j], carry);
+            t[j - 1] = lo;
+            carry = hi;
+        }
+        let (s, c) = adc(t[4], carry, 0);
+        t[3] = s;
+        t[4] = t[5] + c;
+    }
+
+    reduce_once([t[0], t[1], t[2], t[3]], t[4])
+}
+
+fn is_canonical(limbs: &[u64; 4]) -> bool {
+    for i in (0..4).rev() {
+        if limbs[i] != P[i] {
+            return limbs[i] < P[i];
+        }
+    }
+
+    false
+}
+
+impl FieldElement {
+    pub fn from_u64(value: u64) -> Self {
+        FieldElement(mont_mul(&[value, 0, 0, 0], &R2))
+    }
+
+    fn limbs_from_bytes(bytes: &[u8]) -> [u64; 4] {
+        let mut limbs = [0u64; 4];
+
+        for i in 0..4 {
+            let mut limb = [0u8; 8];
+            limb.copy_from_slice(&bytes[(24 - i * 8)..(32 - i * 8)]);
+            limbs[i] = u64::from_be_bytes(limb);
+        }
+
+        limbs
+    }
+
+    pub fn to_biguint(&self) -> BigUint {
+        BigUint::from_bytes_be(&self.to_bytes_be())
+    }
+}
+
+impl Field for FieldElement {
+    const ZERO: FieldElement = FieldElement([0, 0, 0, 0]);
+    const ONE: FieldElement = FieldElement(R);
+
+    const ELEMENT_BYTES: usize = 32;
+
+    // MODULUS - 1 = 2^32 * (2^224 - 351)
+    const TWO_ADICITY: u32 = 32;
+
+    const FIELD_ID: u32 = 0x0101;
+
+    fn inv(&self) -> Self {
+        self.pow_limbs(&P_MINUS_2)
+    }
+
+    // 7^((MODULUS - 1) / order)
+    fn get_root_of_unity(order: u64) -> Self {
+        assert!(order.is_power_of_two() && order.trailing_zeros() <= Self::TWO_ADICITY, "no subgroup of order {}", order);
+
+        let mut root = FieldElement(TWO_ADIC_ROOT);
+        for _ in order.trailing_zeros()..Self::TWO_ADICITY {
+            root = root.square();
+        }
+
+        root
+    }
+
+    fn from_bytes_be(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::ELEMENT_BYTES {
+            return None;
+        }
+
+        let limbs = Self::limbs_from_bytes(bytes);
+
+        if !is_canonical(&limbs) {
+            return None;
+        }
+
+        Some(FieldElement(mont_mul(&limbs, &R2)))
+    }
+
+    fn to_bytes_be(&self) -> Vec<u8> {
+        let limbs = mont_mul(&self.0, &[1, 0, 0, 0]);
+        let mut bytes = vec![0u8; 32];
+
+        for i in 0..4 {
+            bytes[(24 - i * 8)..(32 - i * 8)].copy_from_slice(&limbs[i].to_be_bytes());
+        }
+
+        bytes
+    }
+
+    fn from_bytes_be_reduced(bytes: &[u8; 32]) -> Self {
+        // the modulus is above 2^255 so a single subtraction is enough
+        let limbs = reduce_once(Self::limbs_from_bytes(bytes), 0);
+        FieldElement(mont_mul(&limbs, &R2))
+    }
+
+    fn square(&self) -> Self {
+        FieldElement(mont_mul(&self.0, &self.0))
+    }
+}
+
+impl From<u64> for FieldElement {
+    fn from(value: u64) -> Self {
+        Self::from_u64(value)
+    }
+}
+
+impl Add for FieldElement {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        let mut r = [0u64; 4];
+        let mut carry = 0;
+
+        for (i, limb) in r.iter_mut().enumerate() {
+            let (s, c) = adc(self.0[i], rhs.0[i], carry);
+            *limb = s;
+            carry = c;
+        }
+
+        FieldElement(reduce_once(r, carry))
+    }
+}
+
+impl Sub for FieldElement {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        let mut r = [0u64; 4];
+        let mut borrow = 0;
+
+        for (i, limb) in r.iter_mut().enumerate() {
+            let (d, b) = sbb(self.0[i], rhs.0[i], borrow);
+            *limb = d;
+            borrow = b;
+        }
+
+        // wrapped around, add the modulus back
+        if borrow != 0 {
+            let mut carry = 0;
+            for i in 0..4 {
+                let (s, c) = adc(r[i], P[i], carry);
+                r[i] = s;
+                carry = c;
+            }
+        }
+
+        FieldElement(r)
+    }
+}
+
+impl Mul for FieldElement {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        FieldElement(mont_mul(&self.0, &rhs.0))
+    }
+}
+
+impl Neg for FieldElement {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+impl AddAssign for FieldElement {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for FieldElement {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for FieldElement {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl fmt::Display for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_biguint())
+    }
+}
+
+impl fmt::Debug for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_biguint())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::tests::{check_field, elements};
+    use crate::MODULUS;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_arithmetic_matches_biguint() {
+        let modulus = BigUint::from_str(MODULUS).unwrap();
+        let values: Vec<FieldElement> = elements(64);
+
+        for a in &values {
+            for b in &values[..16] {
+                let (x, y) = (a.to_biguint(), b.to_biguint());
+
+                assert_eq!((*a + *b).to_biguint(), (&x + &y) % &modulus);
+                assert_eq!((*a - *b).to_biguint(), (&x + &modulus - &y) % &modulus);
+                assert_eq!((*a * *b).to_biguint(), (&x * &y) % &modulus);
+            }
+
+            assert_eq!((-*a).to_biguint(), (&modulus - a.to_biguint()) % &modulus);
+        }
+    }
+
+    #[test]
+    fn test_field() {
+        check_field::<FieldElement>();
+    }
+
+    #[test]
+    fn test_serialization() {
+        let modulus_bytes = BigUint::from_str(MODULUS).unwrap().to_bytes_be();
+        assert_eq!(FieldElement::from_bytes_be(&modulus_bytes), None);
+        assert_eq!(FieldElement::from_bytes_be_reduced(&[0xff; 32]), FieldElement::from_u64(351 * (1 << 32) - 2));
+        assert_eq!(FieldElement::from_u64(42).to_string(), "42");
+    }
+
+    #[test]
+    fn test_root_of_unity() {
+        let modulus = BigUint::from_str(MODULUS).unwrap();
+        let expected = BigUint::from(7u8).modpow(&((&modulus - 1u8) >> 32), &modulus);
+        let root = FieldElement::get_root_of_unity(1 << 32);
+
+        assert_eq!(root.to_biguint(), expected);
+        assert_eq!(root.pow(1 << 31), -FieldElement::ONE);
+        assert_eq!(FieldElement::get_root_of_unity(8).pow(4), -FieldElement::ONE);
+        assert_eq!(FieldElement::get_root_of_unity(1), FieldElement::ONE);
+    }
+}
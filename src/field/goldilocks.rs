@@ -0,0 +1,233 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use super::{ExtensibleField, Field};
+
+// 2^64 - 2^32 + 1
+const P: u64 = 0xffffffff00000001;
+// 2^64 mod P
+const EPSILON: u64 = 0xffffffff;
+
+/// An element of the Goldilocks field of 2^64 - 2^32 + 1, stored canonically (less than
+/// the modulus). Products fit in a u128 and reduce with a few shifts and adds.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct Goldilocks(u64);
+
+// x = lo + hi_lo * 2^64 + hi_hi * 2^96 where 2^64 = EPSILON and 2^96 = -1 mod P
+#[inline(always)]
+fn reduce128(x: u128) -> u64 {
+    let lo = x as u64;
+    let hi = (x >> 64) as u64;
+    let hi_hi = hi >> 32;
+    let hi_lo = hi & EPSILON;
+
+    let (mut t0, borrow) = lo.overflowing_sub(hi_hi);
+    if borrow {
+        // the subtraction wrapped around by 2^64, which is EPSILON too many
+        t0 = t0.wrapping_sub(EPSILON);
+    }
+
+    let (t1, carry) = t0.overflowing_add(hi_lo * EPSILON);
+    let res = if carry { t1 + EPSILON } else { t1 };
+
+    if res >= P { res - P } else { res }
+}
+
+impl Goldilocks {
+    pub const MODULUS: u64 = P;
+
+    pub fn from_u64(value: u64) -> Self {
+        Goldilocks(if value >= P { value - P } else { value })
+    }
+
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Field for Goldilocks {
+    const ZERO: Goldilocks = Goldilocks(0);
+    const ONE: Goldilocks = Goldilocks(1);
+
+    const ELEMENT_BYTES: usize = 8;
+
+    // P - 1 = 2^32 * (2^32 - 1)
+    const TWO_ADICITY: u32 = 32;
+
+    const FIELD_ID: u32 = 0x0102;
+
+    fn inv(&self) -> Self {
+        self.pow(P - 2)
+    }
+
+    // 7 generates the multiplicative group, so 7^((P - 1) / order) has exactly that order
+    fn get_root_of_unity(order: u64) -> Self {
+        assert!(order.is_power_of_two() && order.trailing_zeros() <= Self::TWO_ADICITY, "no subgroup of order {}", order);
+
+        Goldilocks(7).pow((P - 1) >> order.trailing_zeros())
+    }
+
+    fn from_bytes_be(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::ELEMENT_BYTES {
+            return None;
+        }
+
+        let mut value = [0u8; 8];
+        value.copy_from_slice(bytes);
+        let value = u64::from_be_bytes(value);
+
+        if value >= P {
+            return None;
+        }
+
+        Some(Goldilocks(value))
+    }
+
+    fn to_bytes_be(&self) -> Vec<u8> {
+        self.0.to_be_bytes().to_vec()
+    }
+
+    fn from_bytes_be_reduced(bytes: &[u8; 32]) -> Self {
+        let mut acc = 0u64;
+
+        for chunk in bytes.chunks(8) {
+            let mut limb = [0u8; 8];
+            limb.copy_from_slice(chunk);
+            acc = reduce128(((acc as u128) << 64) | u64::from_be_bytes(limb) as u128);
+        }
+
+        Goldilocks(acc)
+    }
+}
+
+impl ExtensibleField for Goldilocks {
+    const NONRESIDUE: Goldilocks = Goldilocks(7);
+}
+
+impl From<u64> for Goldilocks {
+    fn from(value: u64) -> Self {
+        Self::from_u64(value)
+    }
+}
+
+impl Add for Goldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        let (sum, over) = self.0.overflowing_add(rhs.0);
+
+        if over {
+            // the true sum is below 2P, sum + 2^64 - P can't overflow again
+            Goldilocks(sum + EPSILON)
+        } else if sum >= P {
+            Goldilocks(sum - P)
+        } else {
+            Goldilocks(sum)
+        }
+    }
+}
+
+impl Sub for Goldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        let (diff, borrow) = self.0.overflowing_sub(rhs.0);
+
+        // adding P back is the same as subtracting EPSILON modulo 2^64
+        Goldilocks(if borrow { diff.wrapping_sub(EPSILON) } else { diff })
+    }
+}
+
+impl Mul for Goldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Goldilocks(reduce128((self.0 as u128) * (rhs.0 as u128)))
+    }
+}
+
+impl Neg for Goldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+impl AddAssign for Goldilocks {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Goldilocks {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for Goldilocks {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl fmt::Display for Goldilocks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Debug for Goldilocks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::tests::{check_field, elements};
+
+    #[test]
+    fn test_field() {
+        check_field::<Goldilocks>();
+    }
+
+    #[test]
+    fn test_arithmetic_matches_u128() {
+        let values: Vec<Goldilocks> = elements(64);
+        let p = P as u128;
+
+        for a in &values {
+            for b in &values[..16] {
+                let (x, y) = (a.0 as u128, b.0 as u128);
+
+                assert_eq!((*a + *b).0 as u128, (x + y) % p);
+                assert_eq!((*a - *b).0 as u128, (x + p - y) % p);
+                assert_eq!((*a * *b).0 as u128, (x * y) % p);
+            }
+        }
+    }
+
+    #[test]
+    fn test_serialization() {
+        assert_eq!(Goldilocks::from_bytes_be(&P.to_be_bytes()), None);
+        assert_eq!(Goldilocks::from_bytes_be(&(P - 1).to_be_bytes()), Some(-Goldilocks::ONE));
+
+        // 2^256 - 1 = (2^64)^4 - 1 = EPSILON^4 - 1
+        let expected = Goldilocks(EPSILON).pow(4) - Goldilocks::ONE;
+        assert_eq!(Goldilocks::from_bytes_be_reduced(&[0xff; 32]), expected);
+    }
+
+    #[test]
+    fn test_nonresidue() {
+        // 7 is neither a square nor a cube, so x^2 - 7 and x^3 - 7 are irreducible
+        assert_eq!(Goldilocks::NONRESIDUE.pow((P - 1) / 2), -Goldilocks::ONE);
+        assert!(Goldilocks::NONRESIDUE.pow((P - 1) / 3) != Goldilocks::ONE);
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+mod f256;
+mod goldilocks;
+mod extension;
+
+pub use self::f256::FieldElement;
+pub use self::goldilocks::Goldilocks;
+pub use self::extension::{QuadExtension, CubicExtension};
+
+/// A finite field the verifier can run over. Besides the arithmetic, a field has to
+/// provide a large enough power of two subgroup for the evaluation domain and a fixed
+/// size big endian serialization for the commitments.
+pub trait Field:
+    Copy + Eq + Hash + Default + Debug + Display + From<u64> + Send + Sync + 'static
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self>
+    + AddAssign + SubAssign + MulAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Number of bytes in a serialized element
+    const ELEMENT_BYTES: usize;
+
+    /// Power of two subgroups have at most 2^TWO_ADICITY elements
+    const TWO_ADICITY: u32;
+
+    /// Identifies the field in proof headers: the extension degree in the second byte and
+    /// the base field in the first
+    const FIELD_ID: u32;
+
+    /// Multiplicative inverse, zero is mapped to zero
+    fn inv(&self) -> Self;
+
+    /// Generator of the subgroup of `order` elements, where `order` is a power of two of
+    /// at most 2^TWO_ADICITY
+    fn get_root_of_unity(order: u64) -> Self;
+
+    /// Parses a big endian encoding of ELEMENT_BYTES bytes, which has to be canonical
+    /// (less than the modulus)
+    fn from_bytes_be(bytes: &[u8]) -> Option<Self>;
+
+    /// Big endian encoding of ELEMENT_BYTES bytes
+    fn to_bytes_be(&self) -> Vec<u8>;
+
+    /// Interprets 32 bytes (e.g. a hash) as a big endian integer and reduces it
+    fn from_bytes_be_reduced(bytes: &[u8; 32]) -> Self;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::ZERO
+    }
+
+    fn square(&self) -> Self {
+        *self * *self
+    }
+
+    fn pow(&self, exp: u64) -> Self {
+        let mut res = Self::ONE;
+
+        for i in (0..(64 - exp.leading_zeros())).rev() {
+            res = res.square();
+
+            if (exp >> i) & 1 == 1 {
+                res *= *self;
+            }
+        }
+
+        res
+    }
+
+    /// Exponentiation by an arbitrary size exponent given as little endian u64 limbs
+    fn pow_limbs(&self, exp: &[u64]) -> Self {
+        let mut res = Self::ONE;
+
+        for limb in exp.iter().rev() {
+            for i in (0..64).rev() {
+                res = res.square();
+
+                if (limb >> i) & 1 == 1 {
+                    res *= *self;
+                }
+            }
+        }
+
+        res
+    }
+}
+
+/// A field that can be extended with u^2 = NONRESIDUE or u^3 = NONRESIDUE, the verifier's
+/// challenges can then be drawn from QuadExtension or CubicExtension when the field itself
+/// is too small for them.
+pub trait ExtensibleField: Field {
+    /// An element that is neither a square nor a cube
+    const NONRESIDUE: Self;
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // deterministic stream of field elements covering the whole range
+    pub fn elements<F: Field>(n: usize) -> Vec<F> {
+        let mut state: u64 = 0x2545f4914f6cdd1d;
+        let mut output = vec![F::ZERO, F::ONE, -F::ONE];
+
+        while output.len() < n {
+            let mut bytes = [0u8; 32];
+            for chunk in bytes.chunks_mut(8) {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                chunk.copy_from_slice(&state.to_be_bytes());
+            }
+            output.push(F::from_bytes_be_reduced(&bytes));
+        }
+
+        output
+    }
+
+    // properties every Field implementation has to satisfy
+    pub fn check_field<F: Field>() {
+        let values: Vec<F> = elements(32);
+
+        for a in &values {
+            for b in &values[..8] {
+                assert_eq!(*a + *b, *b + *a);
+                assert_eq!(*a * *b, *b * *a);
+                assert_eq!(*a + *b - *b, *a);
+                assert_eq!((*a + *b) * *a, a.square() + *a * *b);
+            }
+
+            assert_eq!(*a + -*a, F::ZERO);
+            assert_eq!(a.pow(5), *a * a.square().square());
+            assert_eq!(F::from_bytes_be(&a.to_bytes_be()), Some(*a));
+            assert_eq!(a.to_bytes_be().len(), F::ELEMENT_BYTES);
+
+            if a.is_zero() {
+                assert_eq!(a.inv(), F::ZERO);
+            } else {
+                assert_eq!(*a * a.inv(), F::ONE);
+            }
+        }
+
+        assert_eq!(F::from_bytes_be(&[0xff; 1]), None);
+        assert_eq!(F::from(3) * F::from(5), F::from(15));
+
+        for log_order in 0..(F::TWO_ADICITY + 1) {
+            let root = F::get_root_of_unity(1 << log_order);
+
+            assert_eq!(root.pow(1 << log_order), F::ONE);
+            if log_order > 0 {
+                assert_eq!(root.pow(1 << (log_order - 1)), -F::ONE);
+            }
+        }
+    }
+}
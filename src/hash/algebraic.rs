@@ -0,0 +1,156 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use lazy_static::lazy_static;
+use sha3::Shake256;
+use sha3::digest::{ExtendableOutput, Input, XofReader};
+
+use crate::field::{Field, FieldElement, Goldilocks};
+use crate::merkle_tree::MerkleDigest;
+
+/// Security level the number of rounds and the constants are derived for
+pub const SECURITY_LEVEL: usize = 128;
+
+/// Parameters of the `RescueLike` and `PoseidonLike` sponges over a field. Digests are the first
+/// 32 / ELEMENT_BYTES elements of the rate, so that they serialize to 32 bytes.
+pub trait AlgebraicHashField: Field {
+    /// The modulus in decimal, part of the seed the round constants are derived from
+    const MODULUS: &'static str;
+
+    /// Number of elements in the permutation's state
+    const STATE_WIDTH: usize;
+
+    /// Number of elements absorbed per permutation, the rest of the state is the capacity
+    const RATE: usize;
+
+    /// Exponent of the S-box, the smallest alpha with gcd(alpha, p - 1) = 1
+    const ALPHA: u64;
+
+    /// alpha^-1 mod p - 1 as little endian u64 limbs, the exponent of Rescue's inverse S-box
+    const ALPHA_INV: &'static [u64];
+
+    /// Rounds of Rescue-Prime, from the formula of the specification
+    const RESCUE_ROUNDS: usize;
+
+    const POSEIDON_FULL_ROUNDS: usize;
+    const POSEIDON_PARTIAL_ROUNDS: usize;
+}
+
+impl AlgebraicHashField for FieldElement {
+    const MODULUS: &'static str = crate::MODULUS;
+    const STATE_WIDTH: usize = 3;
+    const RATE: usize = 2;
+    const ALPHA: u64 = 3;
+    const ALPHA_INV: &'static [u64] = &[0xaaaaa9c0aaaaaaab, 0xaaaaaaaaaaaaaaaa, 0xaaaaaaaaaaaaaaaa, 0xaaaaaaaaaaaaaaaa];
+    const RESCUE_ROUNDS: usize = 35;
+    const POSEIDON_FULL_ROUNDS: usize = 8;
+    const POSEIDON_PARTIAL_ROUNDS: usize = 84;
+}
+
+impl AlgebraicHashField for Goldilocks {
+    const MODULUS: &'static str = "18446744069414584321";
+    const STATE_WIDTH: usize = 12;
+    const RATE: usize = 8;
+    const ALPHA: u64 = 7;
+    const ALPHA_INV: &'static [u64] = &[0x92492491b6db6db7];
+    const RESCUE_ROUNDS: usize = 8;
+    const POSEIDON_FULL_ROUNDS: usize = 8;
+    const POSEIDON_PARTIAL_ROUNDS: usize = 22;
+}
+
+lazy_static! {
+    // the constants and MDS matrices are derived once per hash and field
+    static ref PARAMETERS: Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>> = Mutex::new(HashMap::new());
+}
+
+pub(crate) fn cached<T: Any + Send + Sync, G: FnOnce() -> T>(build: G) -> Arc<T> {
+    let key = TypeId::of::<T>();
+
+    if let Some(params) = PARAMETERS.lock().unwrap().get(&key) {
+        return params.clone().downcast::<T>().unwrap();
+    }
+
+    let params = Arc::new(build());
+    PARAMETERS.lock().unwrap().insert(key, params.clone());
+
+    params
+}
+
+pub(crate) trait Permutation<F: AlgebraicHashField> {
+    fn permute(&self, state: &mut [F]);
+}
+
+/// `count` elements from SHAKE256(seed), each read from ceil(log2(p) / 8) + 1 little
+/// endian bytes and reduced, as in the Rescue-Prime specification
+pub(crate) fn round_constants<F: AlgebraicHashField>(seed: &str, count: usize) -> Vec<F> {
+    let bytes_per_int = (F::ELEMENT_BYTES * 8 - leading_zero_bits::<F>()).div_ceil(8) + 1;
+    let mut stream = vec![0u8; bytes_per_int * count];
+
+    let mut shake = Shake256::default();
+    shake.input(seed.as_bytes());
+    shake.xof_result().read(&mut stream);
+
+    stream.chunks(bytes_per_int).map(|chunk| {
+        chunk.iter().rev().fold(F::ZERO, |acc, byte| acc * F::from(256) + F::from(*byte as u64))
+    }).collect()
+}
+
+// leading zero bits of the modulus in its ELEMENT_BYTES encoding
+fn leading_zero_bits<F: AlgebraicHashField>() -> usize {
+    let max = (-F::ONE).to_bytes_be();
+    let mut zeros = 0;
+
+    for byte in max {
+        if byte != 0 {
+            return zeros + byte.leading_zeros() as usize;
+        }
+        zeros += 8;
+    }
+
+    zeros
+}
+
+pub(crate) fn apply_mds<F: Field>(mds: &[Vec<F>], state: &mut [F]) {
+    let result: Vec<F> = mds.iter().map(|row| {
+        row.iter().zip(state.iter()).fold(F::ZERO, |acc, (a, b)| acc + *a * *b)
+    }).collect();
+
+    state.copy_from_slice(&result);
+}
+
+/// Hashes `data` with the sponge construction of the Rescue-Prime specification. The length
+/// of `data` in bytes is absorbed first, then the bytes in chunks of ELEMENT_BYTES - 1 read
+/// as big endian integers, which are always below the modulus, so different data never
+/// absorbs the same elements. They are padded with a one and zeros to a multiple of the
+/// rate and added into the rate one block per permutation.
+pub(crate) fn sponge<F: AlgebraicHashField, P: Permutation<F>>(permutation: &P, data: &[u8]) -> MerkleDigest {
+    let chunk_size = F::ELEMENT_BYTES - 1;
+    let mut elements: Vec<F> = vec![F::from(data.len() as u64)];
+
+    elements.extend(data.chunks(chunk_size).map(|chunk| {
+        let mut padded = vec![0u8; F::ELEMENT_BYTES];
+        padded[(F::ELEMENT_BYTES - chunk.len())..].copy_from_slice(chunk);
+        F::from_bytes_be(&padded).expect("a chunk is shorter than the modulus")
+    }));
+
+    elements.push(F::ONE);
+    elements.resize(elements.len().div_ceil(F::RATE) * F::RATE, F::ZERO);
+
+    let mut state = vec![F::ZERO; F::STATE_WIDTH];
+
+    for block in elements.chunks(F::RATE) {
+        for (s, x) in state.iter_mut().zip(block.iter()) {
+            *s += *x;
+        }
+
+        permutation.permute(&mut state);
+    }
+
+    let digest_elements = 32 / F::ELEMENT_BYTES;
+    let bytes: Vec<u8> = state[..digest_elements].iter().flat_map(|x| x.to_bytes_be()).collect();
+
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&bytes);
+    digest
+}
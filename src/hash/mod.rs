@@ -0,0 +1,106 @@
+use blake2::{Blake2s, VarBlake2b};
+use blake2::digest::{Digest, Input, VariableOutput};
+use sha3::Keccak256 as Keccak;
+
+use crate::merkle_tree::MerkleDigest;
+
+mod algebraic;
+mod rescue;
+mod poseidon;
+
+pub use self::algebraic::AlgebraicHashField;
+pub use self::rescue::RescueLike;
+pub use self::poseidon::PoseidonLike;
+
+/// Hash function used for the Merkle commitments and to derive the verifier's challenges.
+/// Digests are always 32 bytes, the size of roots and witnesses in the proof format.
+pub trait Hasher {
+    /// Number of bytes in a digest
+    const DIGEST_SIZE: usize = 32;
+
+    /// Hashes a leaf value, or any other data (e.g. for Fiat-Shamir)
+    fn hash_leaf(data: &[u8]) -> MerkleDigest;
+
+    /// Hashes two children into their parent. At the lowest level of the tree the children
+    /// are raw leaf values, above they are digests.
+    fn hash_children(left: &[u8], right: &[u8]) -> MerkleDigest {
+        Self::hash_leaf(&[left, right].concat())
+    }
+}
+
+// copies the output of a 32 byte hash
+fn to_digest(bytes: &[u8]) -> MerkleDigest {
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(bytes);
+    digest
+}
+
+/// Blake2s with 256 bit output, used by the python prover
+pub struct Blake2s256;
+
+/// Blake2b truncated to a 256 bit output (as a parameter, not by cutting the 512 bit digest)
+pub struct Blake2b256;
+
+/// SHA-256
+pub struct Sha256;
+
+/// Keccak-256 as used by the EVM, which is not the padding of the standardized SHA3-256
+pub struct Keccak256;
+
+/// BLAKE3 with the default 256 bit output
+pub struct Blake3;
+
+impl Hasher for Blake2s256 {
+    fn hash_leaf(data: &[u8]) -> MerkleDigest {
+        to_digest(&Blake2s::digest(data))
+    }
+}
+
+impl Hasher for Blake2b256 {
+    fn hash_leaf(data: &[u8]) -> MerkleDigest {
+        let mut digest = [0u8; 32];
+        let mut hasher = VarBlake2b::new(32).unwrap();
+
+        hasher.input(data);
+        hasher.variable_result(|res| digest.copy_from_slice(res));
+        digest
+    }
+}
+
+impl Hasher for Sha256 {
+    fn hash_leaf(data: &[u8]) -> MerkleDigest {
+        to_digest(&sha2::Sha256::digest(data))
+    }
+}
+
+impl Hasher for Keccak256 {
+    fn hash_leaf(data: &[u8]) -> MerkleDigest {
+        to_digest(&Keccak::digest(data))
+    }
+}
+
+impl Hasher for Blake3 {
+    fn hash_leaf(data: &[u8]) -> MerkleDigest {
+        *blake3::hash(data).as_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<H: Hasher>(expected: &str) {
+        assert_eq!(hex::encode(H::hash_leaf(b"abc")), expected);
+        assert_eq!(H::hash_children(b"ab", b"c"), H::hash_leaf(b"abc"));
+        assert_eq!(H::DIGEST_SIZE, 32);
+    }
+
+    #[test]
+    fn test_known_answers() {
+        check::<Blake2s256>("508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982");
+        check::<Blake2b256>("bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319");
+        check::<Sha256>("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        check::<Keccak256>("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45");
+        check::<Blake3>("6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85");
+    }
+}
@@ -0,0 +1,108 @@
+use std::marker::PhantomData;
+
+use super::Hasher;
+use super::algebraic::{apply_mds, cached, round_constants, sponge, AlgebraicHashField, Permutation, SECURITY_LEVEL};
+use crate::merkle_tree::MerkleDigest;
+
+/// A sponge with the round structure of Poseidon over the field F, with x^alpha S-boxes. The
+/// round constants come from SHAKE256 like `RescueLike`'s rather than from the reference
+/// Grain LFSR and the MDS matrix is the Cauchy matrix 1 / (i + (m + j)), so it is named apart
+/// from Poseidon: its digests differ from every Poseidon instantiation.
+pub struct PoseidonLike<F: AlgebraicHashField>(PhantomData<F>);
+
+struct PoseidonParams<F: AlgebraicHashField> {
+    mds: Vec<Vec<F>>,
+    round_constants: Vec<F>,
+}
+
+impl<F: AlgebraicHashField> PoseidonParams<F> {
+    fn new() -> Self {
+        let m = F::STATE_WIDTH;
+        let rounds = F::POSEIDON_FULL_ROUNDS + F::POSEIDON_PARTIAL_ROUNDS;
+        let seed = format!("Poseidon({},{},{},{})", F::MODULUS, m, m - F::RATE, SECURITY_LEVEL);
+
+        // the x_i = i and y_j = m + j are distinct and all sums are non zero
+        let mds = (0..m).map(|i| {
+            (0..m).map(|j| F::from((i + m + j) as u64).inv()).collect()
+        }).collect();
+
+        PoseidonParams {
+            mds,
+            round_constants: round_constants(&seed, rounds * m),
+        }
+    }
+}
+
+impl<F: AlgebraicHashField> Permutation<F> for PoseidonParams<F> {
+    // half of the full rounds, the partial rounds (S-box on the first element only), then
+    // the other half of the full rounds
+    fn permute(&self, state: &mut [F]) {
+        let half_full = F::POSEIDON_FULL_ROUNDS / 2;
+
+        for (round, constants) in self.round_constants.chunks(F::STATE_WIDTH).enumerate() {
+            for (x, c) in state.iter_mut().zip(constants) {
+                *x += *c;
+            }
+
+            if round < half_full || round >= half_full + F::POSEIDON_PARTIAL_ROUNDS {
+                for x in state.iter_mut() {
+                    *x = x.pow(F::ALPHA);
+                }
+            } else {
+                state[0] = state[0].pow(F::ALPHA);
+            }
+
+            apply_mds(&self.mds, state);
+        }
+    }
+}
+
+impl<F: AlgebraicHashField> PoseidonLike<F> {
+    /// Applies the permutation to a full state of STATE_WIDTH elements
+    pub fn permute(state: &mut [F]) {
+        assert_eq!(state.len(), F::STATE_WIDTH, "wrong state width");
+        cached(PoseidonParams::<F>::new).permute(state);
+    }
+}
+
+impl<F: AlgebraicHashField> Hasher for PoseidonLike<F> {
+    fn hash_leaf(data: &[u8]) -> MerkleDigest {
+        sponge(&*cached(PoseidonParams::<F>::new), data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{FieldElement, Goldilocks};
+
+    // regression snapshots of this implementation, not vectors from a reference one: they
+    // catch changes to the construction but can't tell a wrong constant or MDS matrix
+    #[test]
+    fn test_permutation_snapshots() {
+        let mut state: Vec<Goldilocks> = (0..12).map(Goldilocks::from).collect();
+        PoseidonLike::permute(&mut state);
+
+        let expected: Vec<String> = ["11554233945237112673", "16868259818696440312", "10445706891659383363", "2333330190928341701",
+            "1986921635975608257", "14782653868488036064", "2447008458744715945", "2792505248610692956",
+            "5801992633341628007", "11720800460724000988", "5482523748718910166", "8548299031259396119"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(state.iter().map(|x| x.to_string()).collect::<Vec<String>>(), expected);
+
+        let mut state: Vec<FieldElement> = (0..3).map(FieldElement::from).collect();
+        PoseidonLike::permute(&mut state);
+
+        assert_eq!(state[0].to_string(), "108840763523353644126169088070017821707713409200094872534005070296691872357090");
+        assert_eq!(state[1].to_string(), "50071882593188921677955342046490229351264968475079908684689246439218145898074");
+        assert_eq!(state[2].to_string(), "22283601935136696964063166268058214670974492268010068448119092587239932136455");
+    }
+
+    #[test]
+    fn test_hash_snapshots() {
+        assert_eq!(hex::encode(PoseidonLike::<Goldilocks>::hash_leaf(b"abc")), "11b43be9193b061817c9f2514990b2b57183050a1c6027d1d07e581a5732a985");
+        assert_eq!(hex::encode(PoseidonLike::<Goldilocks>::hash_leaf(b"")), "31852d65071ea154fdaa52146c1a9abfe4ebbf524148e09a222fdd8b1ba9cf63");
+        assert_eq!(hex::encode(PoseidonLike::<Goldilocks>::hash_children(&[1u8; 32], &[2u8; 32])), "253753ce2b6b3a2aa486625ced451f808ca3af352baa7ddd6b7272e0d971fe38");
+
+        assert_eq!(hex::encode(PoseidonLike::<FieldElement>::hash_leaf(b"abc")), "27c32c89e25efb8f081f085c72044435dda81efb9e6947adc345f3f8a5b664a1");
+        assert_eq!(hex::encode(PoseidonLike::<FieldElement>::hash_children(&[1u8; 32], &[2u8; 32])), "868c8d90f1d7c331aec590b0beb147ffd88a8abab1a0341943d000b7a80cdf5d");
+    }
+}
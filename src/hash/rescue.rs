@@ -0,0 +1,167 @@
+use std::marker::PhantomData;
+
+use super::Hasher;
+use super::algebraic::{apply_mds, cached, round_constants, sponge, AlgebraicHashField, Permutation, SECURITY_LEVEL};
+use crate::merkle_tree::MerkleDigest;
+
+/// A sponge with the round structure of Rescue-Prime (Rescue-XLIX) over the field F, its MDS
+/// matrix and round constants generated as the specification describes with 7 as the
+/// primitive element. It isn't checked against the reference implementation and encodes bytes
+/// its own way, so it is named apart from Rescue-Prime: its digests only match this crate.
+pub struct RescueLike<F: AlgebraicHashField>(PhantomData<F>);
+
+struct RescueParams<F: AlgebraicHashField> {
+    mds: Vec<Vec<F>>,
+    round_constants: Vec<F>,
+}
+
+// the transposed right half of the reduced echelon form of the m x 2m Vandermonde
+// matrix with entries g^(i * j)
+fn mds_matrix<F: AlgebraicHashField>() -> Vec<Vec<F>> {
+    let m = F::STATE_WIDTH;
+    let g = F::from(7);
+
+    let mut v: Vec<Vec<F>> = (0..m).map(|i| {
+        (0..2 * m).map(|j| g.pow((i * j) as u64)).collect()
+    }).collect();
+
+    for c in 0..m {
+        let pivot = (c..m).find(|r| !v[*r][c].is_zero()).expect("vandermonde matrix has full rank");
+        v.swap(c, pivot);
+
+        let inv = v[c][c].inv();
+        for x in v[c].iter_mut() {
+            *x *= inv;
+        }
+
+        for r in 0..m {
+            let factor = v[r][c];
+
+            if r != c && !factor.is_zero() {
+                let pivot_row = v[c].clone();
+                for (x, y) in v[r].iter_mut().zip(pivot_row) {
+                    *x -= factor * y;
+                }
+            }
+        }
+    }
+
+    (0..m).map(|i| (0..m).map(|j| v[j][m + i]).collect()).collect()
+}
+
+impl<F: AlgebraicHashField> RescueParams<F> {
+    fn new() -> Self {
+        let m = F::STATE_WIDTH;
+        let seed = format!("Rescue-XLIX({},{},{},{})", F::MODULUS, m, m - F::RATE, SECURITY_LEVEL);
+
+        RescueParams {
+            mds: mds_matrix(),
+            round_constants: round_constants(&seed, 2 * m * F::RESCUE_ROUNDS),
+        }
+    }
+}
+
+impl<F: AlgebraicHashField> Permutation<F> for RescueParams<F> {
+    fn permute(&self, state: &mut [F]) {
+        let m = F::STATE_WIDTH;
+
+        for constants in self.round_constants.chunks(2 * m) {
+            for x in state.iter_mut() {
+                *x = x.pow(F::ALPHA);
+            }
+            apply_mds(&self.mds, state);
+            for (x, c) in state.iter_mut().zip(&constants[..m]) {
+                *x += *c;
+            }
+
+            for x in state.iter_mut() {
+                *x = x.pow_limbs(F::ALPHA_INV);
+            }
+            apply_mds(&self.mds, state);
+            for (x, c) in state.iter_mut().zip(&constants[m..]) {
+                *x += *c;
+            }
+        }
+    }
+}
+
+impl<F: AlgebraicHashField> RescueLike<F> {
+    /// Applies the permutation to a full state of STATE_WIDTH elements
+    pub fn permute(state: &mut [F]) {
+        assert_eq!(state.len(), F::STATE_WIDTH, "wrong state width");
+        cached(RescueParams::<F>::new).permute(state);
+    }
+}
+
+impl<F: AlgebraicHashField> Hasher for RescueLike<F> {
+    fn hash_leaf(data: &[u8]) -> MerkleDigest {
+        sponge(&*cached(RescueParams::<F>::new), data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Field, FieldElement, Goldilocks};
+
+    // regression snapshots of this implementation, not vectors from a reference one: they
+    // catch changes to the construction but can't tell a wrong constant or MDS matrix
+    #[test]
+    fn test_permutation_snapshots() {
+        let mut state: Vec<Goldilocks> = (0..12).map(Goldilocks::from).collect();
+        RescueLike::permute(&mut state);
+
+        let expected: Vec<String> = ["14760905225911863170", "17847308539055343136", "17685025781234751606", "1290194616202087046",
+            "9700643919255918128", "8069948266664995872", "12412679204022416752", "3544169727903472778",
+            "17920275731858070398", "11320947258538293778", "7110737059983007313", "14871558962297168316"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(state.iter().map(|x| x.to_string()).collect::<Vec<String>>(), expected);
+
+        let mut state: Vec<FieldElement> = (0..3).map(FieldElement::from).collect();
+        RescueLike::permute(&mut state);
+
+        assert_eq!(state[0].to_string(), "59927624140773549077141815088623267789018005032430834070016792335694256042797");
+        assert_eq!(state[1].to_string(), "17443786511880583781814126194453398734543754584051231248585425081874351917256");
+        assert_eq!(state[2].to_string(), "114770916297991166565512429530926073318400859369755665332900803977500123075408");
+    }
+
+    #[test]
+    fn test_hash_snapshots() {
+        assert_eq!(hex::encode(RescueLike::<Goldilocks>::hash_leaf(b"abc")), "df118a3a0ffda2f43a6283d7236f28efe64a53241a6112ead9cdc5fb76e7a190");
+        assert_eq!(hex::encode(RescueLike::<Goldilocks>::hash_leaf(b"")), "2f893641d653fa14b09abb4c1e8dbbc15cda8fc05578be8c6b4a84ab5e0b140a");
+        assert_eq!(hex::encode(RescueLike::<Goldilocks>::hash_children(&[1u8; 32], &[2u8; 32])), "8dabefcee4b2f4a11fa5dde9a445cb4d89a4a2c5e5d1754a77d11f305907e1ff");
+
+        assert_eq!(hex::encode(RescueLike::<FieldElement>::hash_leaf(b"abc")), "71c6a0342bf9510fd0c91ebe71311f21d2fb6634d14d4496cc622f5926c254eb");
+        assert_eq!(hex::encode(RescueLike::<FieldElement>::hash_children(&[1u8; 32], &[2u8; 32])), "60781d37372b3264311ce0fa4125503166b84228548e63032f7f86088a3df295");
+    }
+
+    fn check_sponge_encoding<H: Hasher, F: Field>() {
+        // a short last chunk isn't confused with leading zeros
+        assert_ne!(H::hash_leaf(b"\x01"), H::hash_leaf(b"\x00\x01"));
+        assert_ne!(H::hash_leaf(b""), H::hash_leaf(b"\x00"));
+
+        // bytes above the modulus aren't confused with their reduction
+        let mut above = [0u8; 32];
+        for byte in above[(32 - F::ELEMENT_BYTES)..].iter_mut() {
+            *byte = 0xff;
+        }
+        assert_ne!(H::hash_leaf(&above[(32 - F::ELEMENT_BYTES)..]), H::hash_leaf(&F::from_bytes_be_reduced(&above).to_bytes_be()));
+    }
+
+    #[test]
+    fn test_sponge_encoding() {
+        check_sponge_encoding::<RescueLike<Goldilocks>, Goldilocks>();
+        check_sponge_encoding::<RescueLike<FieldElement>, FieldElement>();
+        check_sponge_encoding::<crate::hash::PoseidonLike<Goldilocks>, Goldilocks>();
+        check_sponge_encoding::<crate::hash::PoseidonLike<FieldElement>, FieldElement>();
+    }
+
+    #[test]
+    fn test_inverse_sbox() {
+        // the inverse S-box undoes the S-box
+        let x = Goldilocks::from(123456789);
+        assert_eq!(x.pow(Goldilocks::ALPHA).pow_limbs(Goldilocks::ALPHA_INV), x);
+
+        let x = FieldElement::from(123456789);
+        assert_eq!(x.pow(FieldElement::ALPHA).pow_limbs(FieldElement::ALPHA_INV), x);
+    }
+}
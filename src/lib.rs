@@ -3,227 +3,532 @@ pub mod proof;
 pub mod merkle_tree;
 pub mod fft;
 pub mod deserializer;
+pub mod error;
+pub mod options;
+pub mod field;
+pub mod hash;
+pub mod transcript;
+pub mod serializer;
+pub mod prover;
+pub mod container;
+pub mod air;
+pub mod deep;
+
+use self::proof::StarkProof;
+use self::container::ProofContainer;
+use self::air::{Air, AirContext, MimcAir, MimcPublicInputs, PublicInputs};
+
+use crate::error::VerifyError;
+use crate::field::Field;
+use crate::hash::{Hasher, AlgebraicHashField, Blake2s256, Blake2b256, Sha256, Keccak256, Blake3, RescueLike, PoseidonLike};
+use crate::options::{HashFunction, ProofOptions, TranscriptMode};
+use crate::proof::FRIProof;
+use crate::merkle_tree::{MerkleDigest, Value};
+use crate::transcript::{Transcript, FRI_POINT};
+use crate::utils::{par_map, first_error, multi_interp, eval_poly_at, lagrange_interp};
+use crate::fft::fft_inv;
 
-use num_bigint::{BigInt, BigUint};
-use num_bigint::Sign;
-use rustfft::num_traits::Pow;
-use std::str::FromStr;
+// number of FRI queries interpolated together
+const FRI_QUERY_BATCH: usize = 8;
 
-use std::fs::File;
-use std::io;
-use std::io::prelude::*;
-use std::time::{Duration, Instant};
+pub const MODULUS: &str = "115792089237316195423570985008687907853269984665640564039457584006405596119041";
 
-use blake2::{Blake2b, Blake2s, Digest};
-use std::mem::transmute;
-use self::proof::{StarkProof, LDPMerkleProof, LDPPointsProof};
-use merkle_tree::MultiProof;
+// the leaves of a commitment hold serialized field elements, the one at `offset` is read
+fn read_element<F: Field>(leaf: &[u8], offset: usize, layer: usize, index: u32) -> Result<F, VerifyError> {
+    if leaf.len() < offset + F::ELEMENT_BYTES {
+        return Err(VerifyError::MalformedLeaf { layer, index });
+    }
 
-use crate::proof::FRIProof;
-use crate::utils::{is_power_of_2, get_pseudorandom_indices, mimc, as_u32_le, multi_interp_4, eval_quartic, divmod, eval_poly_at, lagrange_interp_2, mul_polys, negative_to_positive};
-use crate::fft::fft_inv;
-use rustfft::num_traits::sign::Signed;
-use rustfft::num_traits::identities::{One, Zero};
+    F::from_bytes_be(&leaf[offset..offset + F::ELEMENT_BYTES]).ok_or(VerifyError::MalformedLeaf { layer, index })
+}
 
-const EXTENSION_FACTOR: usize = 8;
-const MODULUS: &str = "115792089237316195423570985008687907853269984665640564039457584006405596119041";
+// computes the first FRI layer's polynomial at the given positions
+type ValuesAt<'a, E> = dyn Fn(&[u32]) -> Result<Vec<E>, VerifyError> + Sync + 'a;
 
-fn verify_low_degree_proof(merkle_root: &[u8; 32], mut root_of_unity: BigInt, proof: &FRIProof,  mut max_deg_plus_1: BigInt, modulus: &BigInt, excludeMultiplesOf: Option<u32>) -> bool {
-    let mut test_val = root_of_unity.clone(); 
-    let mut rou_deg: usize = 1;
-    let mut root = merkle_root;
+// Where the verifier gets the values of the polynomial FRI starts from
+enum FirstLayer<'a, E: Field> {
+    // a Merkle root, the first FRI layer opens it
+    Committed(&'a MerkleDigest),
+    // the values at the given positions, computed from other commitments
+    Computed(&'a ValuesAt<'a, E>),
+}
+
+// the rows of a FRI layer's polynomial at the queried positions
+enum LayerValues<E: Field> {
+    Committed(Vec<Value>),
+    Computed(Vec<E>),
+}
+
+impl<E: Field> LayerValues<E> {
+    fn get(&self, i: usize, layer: usize, index: u32) -> Result<E, VerifyError> {
+        match self {
+            LayerValues::Committed(values) => read_element(&values[i], 0, layer, index),
+            LayerValues::Computed(values) => Ok(values[i]),
+        }
+    }
+}
 
-    while test_val != BigInt::from(1u32) {
-        rou_deg = rou_deg * 2;
-        test_val = test_val.modpow(&BigInt::from(2u8), &modulus).clone();
+// The FRI layers hold elements of the challenge field E, the domain is a subgroup of the base field F
+fn verify_low_degree_proof<F: Field, E: Field + From<F>, H: Hasher>(transcript: &mut Transcript<H>, first_layer: FirstLayer<E>, mut root_of_unity: F, proof: &FRIProof<E>,  mut max_deg_plus_1: usize, exclude_multiples_of: Option<u32>, options: &ProofOptions) -> Result<(), VerifyError> {
+    let mut test_val = root_of_unity;
+    let mut rou_deg: usize = 1;
+    let mut root: &MerkleDigest = match first_layer {
+        FirstLayer::Committed(merkle_root) => merkle_root,
+        FirstLayer::Computed(_) => &[0u8; 32],
+    };
+
+    while test_val != F::ONE {
+        rou_deg *= 2;
+        test_val = test_val.square();
     }
-    
-    let mut arg: usize = 0; 
 
-    let mut quartic_roots_of_unity: [BigInt; 4] = [
-        BigInt::from(1u32),
-        root_of_unity.modpow(&BigInt::from(rou_deg / 4), &modulus),
-        root_of_unity.modpow(&BigInt::from(rou_deg / 2), &modulus),
-        root_of_unity.modpow(&BigInt::from(rou_deg * 3 / 4), &modulus)
-    ];
+    let folding_factor = options.fri_folding_factor;
+    let log_folding = folding_factor.trailing_zeros() as usize;
+
+    for (layer, m_proof) in proof.merkle_proofs.iter().enumerate() {
+        // each layer shrinks the domain by the folding factor, make sure there are still at
+        // least 4 values left for the next layer's tree
+        if rou_deg < 4 * folding_factor {
+            return Err(VerifyError::TooManyFriLayers);
+        }
 
-    assert!(&rou_deg == &65536usize, "invalid roudeg");
-    assert!(&quartic_roots_of_unity[3] == &FromStr::from_str("80127877722526290441229381276271393407378829608771736609433200039324583025757").unwrap(), "bad quartic roots of unity..");
+        // the poly tree is over the layer's domain, the column tree over the folded one
+        let depth = rou_deg.trailing_zeros() as usize;
 
-    for m_proof in &proof.merkle_proofs {
-        let special_x = BigInt::from_bytes_be(Sign::Plus, root);
+        // the points x * w^i folded into one value, for w of order `folding_factor`
+        let coset_roots: Vec<F> = (0..folding_factor).map(|i| root_of_unity.pow((rou_deg / folding_factor * i) as u64)).collect();
 
-        let ys = get_pseudorandom_indices(&m_proof.root2, 40, (rou_deg / 4) as u32, excludeMultiplesOf);
+        let special_x: E = transcript.challenge_element(FRI_POINT);
 
-        let column_values = m_proof.column_branches.verify(&ys, None).unwrap();
+        transcript.absorb_commitment(b"fri-column", &m_proof.root2);
+        let ys = transcript.challenge_indices(b"fri-queries", options.num_fri_queries, (rou_deg / folding_factor) as u32, exclude_multiples_of);
+
+        let column_values = m_proof.column_branches.verify::<H>(&ys, None, layer + 2, depth - log_folding, options.transcript, options.fri_folding_factor)?;
 
         let mut poly_positions: Vec<u32> = Vec::new();
 
         for y in &ys {
-            for i in 0..4 {
-                poly_positions.push(y + ( (rou_deg as u32) / 4) * i); 
+            for i in 0..folding_factor {
+                poly_positions.push(y + ((rou_deg / folding_factor) * i) as u32);
             }
         }
 
-        let poly_values = m_proof.poly_branches.verify(&poly_positions, Some(root.clone())).unwrap();
+        let poly_values = match first_layer {
+            FirstLayer::Computed(values_at) if layer == 0 => LayerValues::Computed(values_at(&poly_positions)?),
+            _ => LayerValues::Committed(m_proof.poly_branches.verify::<H>(&poly_positions, Some(*root), layer + 1, depth, options.transcript, options.fri_folding_factor)?),
+        };
+
+        // queries are interpolated in batches sharing one inversion, the batches are independent
+        let batches: Vec<usize> = (0..ys.len()).step_by(FRI_QUERY_BATCH).collect();
 
-        let mut xcoords: Vec<BigInt> = Vec::new();
-        let mut rows: Vec<BigInt> = Vec::new();
+        let results = par_map(&batches, |start| {
+            let queries = *start..(start + FRI_QUERY_BATCH).min(ys.len());
+            let mut xcoords: Vec<E> = Vec::new();
+            let mut rows: Vec<E> = Vec::new();
 
-        for (i, y) in (&ys).iter().enumerate() {
-            let x1 = root_of_unity.modpow(&BigInt::from(*y), &modulus);
+            for i in queries.clone() {
+                let x1 = root_of_unity.pow(ys[i] as u64);
 
-            for j in 0..4 {
-                xcoords.push(((&quartic_roots_of_unity[j]) * &x1) % modulus);
-                rows.push(BigInt::from_bytes_be(Sign::Plus, &poly_values[i*4 + j]));
+                for j in 0..folding_factor {
+                    xcoords.push(E::from(coset_roots[j] * x1));
+                    rows.push(poly_values.get(i*folding_factor + j, layer + 1, poly_positions[i*folding_factor + j])?);
+                }
             }
-        }
 
-        let polys: Vec<BigInt> = multi_interp_4(&xcoords, &rows, modulus);
+            let polys: Vec<E> = multi_interp(&xcoords, &rows, folding_factor);
 
-        for (p, c) in polys.chunks(4).zip(column_values.iter()) {
-            assert!(eval_quartic(&p, &special_x, modulus) == BigInt::from_bytes_be(Sign::Plus, c), "low degree test failed...");
-        }
+            for (p, query) in polys.chunks(folding_factor).zip(queries) {
+                if eval_poly_at(p, special_x) != read_element(&column_values[query], 0, layer + 2, ys[query])? {
+                    return Err(VerifyError::LowDegree { layer, query });
+                }
+            }
+
+            Ok(())
+        });
 
-        root_of_unity = root_of_unity.modpow(&BigInt::from(4u8), &modulus); 
-        max_deg_plus_1 = max_deg_plus_1 / BigInt::from(4u8);
-        rou_deg = rou_deg / 4;
+        first_error(results)?;
+
+        root_of_unity = root_of_unity.pow(folding_factor as u64);
+        max_deg_plus_1 /= folding_factor;
+        rou_deg /= folding_factor;
         root = &m_proof.root2;
     }
 
-    // TODO direct verification of the low degree proof components
-
-    true
-}
+    // Verify the direct components of the proof
+    let data = &proof.points_proof;
 
-pub fn verify_mimc_proof(inp: BigInt, num_steps: usize, round_constants: &Vec<BigInt>, output: BigInt, proof: StarkProof, modulus: &BigInt) -> bool {
+    if max_deg_plus_1 > 16 {
+        return Err(VerifyError::TooFewFriLayers);
+    }
 
-    if num_steps > (2usize.pow(32) / EXTENSION_FACTOR) { //TODO use of floor here?
-        return false;
+    if data.len() != rou_deg {
+        return Err(VerifyError::FinalLayerSize { expected: rou_deg, actual: data.len() });
     }
 
-    if !is_power_of_2(num_steps as u32) || !is_power_of_2(round_constants.len() as u32) {
-        return false;
+    // Check the Merkle root matches up
+    let leaves: Vec<Value> = data.iter().map(|x| x.to_bytes_be()).collect();
+    if &merkle_tree::merkle_root::<H>(&leaves, options.transcript, options.fri_folding_factor) != root {
+        return Err(VerifyError::FinalLayerRootMismatch);
     }
 
-    if (round_constants.len() as u32) > num_steps as u32 {
-        return false;
+    // Check the degree of the data
+    match exclude_multiples_of {
+        None => {
+            let poly = fft_inv(data, &E::from(root_of_unity));
+
+            if poly[max_deg_plus_1..].iter().any(|c| !c.is_zero()) {
+                return Err(VerifyError::FinalLayerDegree);
+            }
+        },
+        Some(exclude) => {
+            // the prover divides by a zerofier that vanishes on the excluded positions, so the
+            // values there aren't on the polynomial: interpolate from the others instead of
+            // running an inverse FFT over the whole subgroup
+            let mut powers: Vec<E> = vec![E::ONE];
+            for _ in 1..data.len() {
+                let next = powers[powers.len()-1] * E::from(root_of_unity);
+                powers.push(next);
+            }
+
+            let pts: Vec<usize> = (0..data.len()).filter(|x| x % (exclude as usize) != 0).collect();
+
+            if pts.len() < max_deg_plus_1 {
+                return Err(VerifyError::FinalLayerTooFewPoints { needed: max_deg_plus_1, available: pts.len() });
+            }
+
+            let xs: Vec<E> = pts[..max_deg_plus_1].iter().map(|x| powers[*x]).collect();
+            let ys: Vec<E> = pts[..max_deg_plus_1].iter().map(|x| data[*x]).collect();
+            let poly = lagrange_interp(&xs, &ys);
+
+            for x in &pts[max_deg_plus_1..] {
+                if eval_poly_at(&poly, powers[*x]) != data[*x] {
+                    return Err(VerifyError::FinalLayerDegree);
+                }
+            }
+        }
     }
 
-    let precision = num_steps * EXTENSION_FACTOR;
-    let G2: BigInt = BigInt::from(7u32).modpow(&((modulus.clone() - BigInt::from(1u32)) / precision), &modulus); // TODO do I need floor() here for some reason?
-    let skips = precision / num_steps;
-    let skips2 = num_steps / round_constants.len();
+    Ok(())
+}
+
+// the challenges depend on the statement and on the commitments made before them, the
+// prover starts from the same transcript
+fn air_transcript<A: Air, H: Hasher>(air: &A, options: &ProofOptions) -> Transcript<H> {
+    let mut transcript = Transcript::<H>::new(options.transcript, b"stark");
+    transcript.absorb(b"options", &options.to_bytes());
+    transcript.absorb(b"trace-length", &(air.trace_length() as u64).to_be_bytes());
+    transcript.absorb(b"public-inputs", &air.public_inputs().to_bytes());
 
-    let val = G2.modpow(&BigInt::from(EXTENSION_FACTOR*skips2), &modulus);
+    transcript
+}
 
-    assert!(num_steps == 8192, "num steps incorrect");
-    assert!(G2 == FromStr::from_str("41913712888260089065520476180880993127517355946012995597287997778376518235852").unwrap(), "G2 isn't correct");
+/// Digest of a MiMC statement for proof headers, see `air::public_input_digest`
+pub fn mimc_public_input_digest<F: AlgebraicHashField>(inputs: &MimcPublicInputs<F>) -> MerkleDigest {
+    air::public_input_digest(&MimcAir::new(inputs.clone()))
+}
 
-    assert!(val == FromStr::from_str("56670364103764250102176604807203318908867195832872336813161821519223575486477").unwrap(), "constants mini polynomial root wasn't correct");
+/// Verifies the proof in a container with `options`, which the options in its header have
+/// to match. The public input digest in the header has to match the statement, only
+/// containers of legacy proofs may leave it out.
+pub fn verify_container<A: Air, E: Field + From<A::BaseField>>(air: &A, container: &ProofContainer<E>, options: &ProofOptions) -> Result<(), VerifyError> {
+    // the header comes with the proof, a forger could pick weaker parameters
+    if container.options != *options {
+        return Err(VerifyError::OptionsMismatch);
+    }
 
-    let constants_mini_polynomial = fft_inv(round_constants, &val, &modulus);
+    match container.public_input_digest {
+        Some(digest) if digest != air::public_input_digest(air) => return Err(VerifyError::PublicInputMismatch),
+        None if options.transcript != TranscriptMode::Legacy => return Err(VerifyError::MissingPublicInputDigest),
+        _ => {},
+    }
 
-    if !verify_low_degree_proof(&proof.l_merkle_root, G2.clone(), &proof.fri_proof, BigInt::from(num_steps * 2), &modulus, Some(EXTENSION_FACTOR as u32)) {
-        return false;
+    verify(air, &container.proof, options)
+}
+
+pub fn verify_mimc_container<F: AlgebraicHashField, E: Field + From<F>>(inputs: &MimcPublicInputs<F>, container: &ProofContainer<E>, options: &ProofOptions) -> Result<(), VerifyError> {
+    verify_container(&MimcAir::new(inputs.clone()), container, options)
+}
+
+/// Verifies a MiMC proof over the base field F, see `verify`
+pub fn verify_mimc_proof<F: AlgebraicHashField, E: Field + From<F>>(inputs: &MimcPublicInputs<F>, proof: &StarkProof<E>, options: &ProofOptions) -> Result<(), VerifyError> {
+    verify(&MimcAir::new(inputs.clone()), proof, options)
+}
+
+/// Verifies that `proof` shows an execution trace satisfying the constraints of `air`.
+/// The trace is over the Air's base field, the random linear combination and the FRI
+/// layers are over E, which is either the base field itself or an extension of it when
+/// the base field is too small for the challenges to be sound.
+pub fn verify<A: Air, E: Field + From<A::BaseField>>(air: &A, proof: &StarkProof<E>, options: &ProofOptions) -> Result<(), VerifyError> {
+    options.validate()?;
+
+    match options.hash_fn {
+        HashFunction::Blake2s => verify_with::<A, E, Blake2s256>(air, proof, options),
+        HashFunction::Blake2b => verify_with::<A, E, Blake2b256>(air, proof, options),
+        HashFunction::Sha256 => verify_with::<A, E, Sha256>(air, proof, options),
+        HashFunction::Keccak256 => verify_with::<A, E, Keccak256>(air, proof, options),
+        HashFunction::Blake3 => verify_with::<A, E, Blake3>(air, proof, options),
+        HashFunction::RescueLike => verify_with::<A, E, RescueLike<A::BaseField>>(air, proof, options),
+        HashFunction::PoseidonLike => verify_with::<A, E, PoseidonLike<A::BaseField>>(air, proof, options),
     }
+}
 
-    let mut hasher = Blake2s::default();
+fn verify_with<A: Air, E: Field + From<A::BaseField>, H: Hasher>(air: &A, proof: &StarkProof<E>, options: &ProofOptions) -> Result<(), VerifyError> {
+    let context = AirContext::new(air, options)?;
 
-    hasher.input(&[&proof.merkle_root[..], &[1u8]].concat());
-    let k1 = BigInt::from_bytes_be(Sign::Plus, &hasher.result());
+    let precision = context.precision;
+    let extension_factor = context.extension_factor;
+    let g2 = context.root_of_unity;
+    let depth = precision.trailing_zeros() as usize;
 
-    hasher = Blake2s::default();
-    hasher.input(&[&proof.merkle_root[..], &[2u8]].concat());
-    let k2 = BigInt::from_bytes_be(Sign::Plus, &hasher.result());
+    let mut transcript = air_transcript::<A, H>(air, options);
 
-    hasher = Blake2s::default();
-    hasher.input(&[&proof.merkle_root[..], &[3u8]].concat());
-    let k3 = BigInt::from_bytes_be(Sign::Plus, &hasher.result());
+    // one pair of coefficients for each register and for each B(x), D(x) of the first
+    // constraint is added as it is and the other constraints get one coefficient each
+    transcript.absorb_commitment(b"trace", &proof.merkle_root);
+    let k: Vec<E> = (0..4 * context.trace_width).map(|_| transcript.challenge_element(b"constraint-coefficient")).collect();
+    let d_coefficients: Vec<E> = (1..context.num_constraints).map(|_| transcript.challenge_element(b"constraint-coefficient")).collect();
+    let periodic_coefficients: Vec<E> = (0..2 * context.periodic_boundaries.len()).map(|_| transcript.challenge_element(b"constraint-coefficient")).collect();
 
-    hasher = Blake2s::default();
-    hasher.input(&[&proof.merkle_root[..], &[4u8]].concat());
-    let k4 = BigInt::from_bytes_be(Sign::Plus, &hasher.result());
+    transcript.absorb_commitment(b"linear-combination", &proof.l_merkle_root);
+    let positions = transcript.challenge_indices(b"positions", options.num_queries, precision as u32, Some(extension_factor as u32));
 
-    let samples: u32 = 80; // spot check security factor
-    let positions = get_pseudorandom_indices(&proof.l_merkle_root, samples as usize, precision as u32, Some(EXTENSION_FACTOR as u32));
+    verify_low_degree_proof::<A::BaseField, E, H>(&mut transcript, FirstLayer::Committed(&proof.l_merkle_root), g2, &proof.fri_proof, context.degree_bound, Some(extension_factor as u32), options)?;
 
     let mut augmented_positions: Vec<u32> = Vec::new();
 
     for p in &positions {
         augmented_positions.push(*p);
-        augmented_positions.push((*p + skips as u32) % precision as u32);
+        augmented_positions.push((*p + extension_factor as u32) % precision as u32);
     }
 
-    let values = proof.merkle_branches.verify(&augmented_positions, Some(proof.merkle_root.clone())).unwrap();
-    let linear_comb_values = proof.linear_comb_branches.verify(&positions, Some(proof.l_merkle_root.clone())).unwrap();
+    let values = proof.merkle_branches.verify::<H>(&augmented_positions, Some(proof.merkle_root), 0, depth, options.transcript, options.fri_folding_factor)?;
+    let linear_comb_values = proof.linear_comb_branches.verify::<H>(&positions, Some(proof.l_merkle_root), 1, depth, options.transcript, options.fri_folding_factor)?;
 
-    let last_step_position = G2.modpow(&BigInt::from((num_steps - 1) * skips), modulus);
+    // P(x) and B(x) have degree < steps, x^adjustment lifts them to the degree bound
+    let adjustment = (context.degree_bound - context.trace_length) as u64;
+    let queries: Vec<usize> = (0..positions.len()).collect();
 
-    for (i, p) in positions.iter().enumerate() {
-        let x = G2.modpow(&BigInt::from(*p), modulus);
-        let n_steps = BigInt::from(num_steps);
-        let x_to_the_steps = x.modpow(&n_steps, modulus);
+    // every position is checked on its own, in parallel with the `parallel` feature
+    let results = par_map(&queries, |i| {
+        let (i, p) = (*i, &positions[*i]);
+        let x = g2.pow(*p as u64);
         let m_branch_1 = &values[i*2];
         let m_branch_2 = &values[i*2 + 1];
-        let l_of_x = BigInt::from_bytes_be(Sign::Plus, &linear_comb_values[i]);
+        let l_of_x: E = read_element(&linear_comb_values[i], 0, 1, *p)?;
+
+        // each trace leaf holds the registers, the D(x) of every constraint, the B(x) of
+        // every register and of every periodic assertion, the leaf of g1 * x is only read
+        // for the registers
+        let (width, num_constraints) = (context.trace_width, context.num_constraints);
+        let mut row: Vec<A::BaseField> = Vec::new();
+        for j in 0..context.leaf_width() {
+            row.push(read_element(m_branch_1, j * A::BaseField::ELEMENT_BYTES, 0, augmented_positions[i*2])?);
+        }
 
-        let p_of_x = BigInt::from_bytes_be(Sign::Plus, &m_branch_1[0..32]);
-        let p_of_g1x = BigInt::from_bytes_be(Sign::Plus, &m_branch_2[0..32]);
-        let d_of_x = BigInt::from_bytes_be(Sign::Plus, &m_branch_1[32..64]);
-        let b_of_x = BigInt::from_bytes_be(Sign::Plus, &m_branch_1[64..96]);
+        let mut next_row: Vec<A::BaseField> = Vec::new();
+        for j in 0..width {
+            next_row.push(read_element(m_branch_2, j * A::BaseField::ELEMENT_BYTES, 0, augmented_positions[i*2 + 1])?);
+        }
 
-        let z_value = divmod(&(&x.modpow(&n_steps, &modulus) - BigInt::one()), &(&x - &last_step_position), &modulus);
+        let (p_of_x, d_of_x) = (&row[..width], &row[width..(width + num_constraints)]);
+        let (b_of_x, periodic_b_of_x) = row[(width + num_constraints)..].split_at(width);
 
-        let k_of_x = eval_poly_at(&constants_mini_polynomial, &x.modpow(&BigInt::from(skips2), modulus), modulus);
+        let z_value = context.transition_zerofier(x);
 
         // Check transition constraints C(P(x)) = Z(x) * D(x)
-        assert!((&p_of_g1x - &p_of_x.pow(3u32) - &k_of_x - &z_value * &d_of_x) % modulus == BigInt::zero(), "invalid proof: transition constraints check failed");
+        let mut c_of_p = vec![A::BaseField::ZERO; num_constraints];
+        air.evaluate_transition(p_of_x, &next_row, &context.periodic_values(x), &mut c_of_p);
 
-        //Check boundary constraints B(x) * Q(x) + I(x) = P(x)
-        let interpolant = lagrange_interp_2(&[BigInt::one(), last_step_position.clone()], &[inp.clone(), output.clone()], modulus);	
-        let zeropoly2 = mul_polys(&vec![-BigInt::one(), BigInt::one()], &vec![-last_step_position.clone(), BigInt::one()], modulus);
+        if c_of_p.iter().zip(d_of_x).any(|(c, d)| *c != z_value * *d) {
+            return Err(VerifyError::TransitionConstraint { position: *p });
+        }
+
+        //Check boundary constraints B(x) * Q(x) + I(x) = P(x) of every register
+        for r in 0..width {
+            if p_of_x[r] != b_of_x[r] * eval_poly_at(&context.boundary_zerofiers[r], x) + eval_poly_at(&context.boundary_interpolants[r], x) {
+                return Err(VerifyError::BoundaryConstraint { position: *p });
+            }
+        }
+
+        // and B(x) * (x^steps - offset) + value = P(x) of every periodic assertion
+        for (boundary, b) in context.periodic_boundaries.iter().zip(periodic_b_of_x) {
+            if p_of_x[boundary.register] != *b * boundary.zerofier(x) + boundary.value {
+                return Err(VerifyError::BoundaryConstraint { position: *p });
+            }
+        }
 
-        assert!(negative_to_positive(&(&p_of_x - &b_of_x * eval_poly_at(&zeropoly2, &x, modulus) - eval_poly_at(&interpolant.to_vec(), &x, modulus)), modulus) == BigInt::zero(), "invalid proof: boundary constraints B(x) * Q(x) + I(x) = P(x) check failed");
-        
         // Check correctness of the linear combination
-        assert!(negative_to_positive(&(&l_of_x - &d_of_x - &k1 * &p_of_x - &k2 * &p_of_x * &x_to_the_steps - 
-            &k3 * &b_of_x - &k4 * &b_of_x * &x_to_the_steps), modulus) == BigInt::zero(), "invalid linear combination");
-    }
+        let x_to_the_adjustment = E::from(x.pow(adjustment));
+
+        let mut expected = E::from(d_of_x[0]);
+        for (r, k) in k.chunks(4).enumerate() {
+            let (p_of_x, b_of_x) = (E::from(p_of_x[r]), E::from(b_of_x[r]));
+            expected += k[0] * p_of_x + k[1] * p_of_x * x_to_the_adjustment + k[2] * b_of_x + k[3] * b_of_x * x_to_the_adjustment;
+        }
+        for (c, d) in d_coefficients.iter().zip(&d_of_x[1..]) {
+            expected += *c * E::from(*d);
+        }
+        for (k, b) in periodic_coefficients.chunks(2).zip(periodic_b_of_x) {
+            let b_of_x = E::from(*b);
+            expected += k[0] * b_of_x + k[1] * b_of_x * x_to_the_adjustment;
+        }
+
+        if l_of_x != expected {
+            return Err(VerifyError::LinearCombination { position: *p });
+        }
 
-    println!("proof verified");
+        Ok(())
+    });
 
-    return true;
+    first_error(results)
 }
 
-/*
-fn main() {
-    let mut file = File::open("proof.bin").unwrap();
-    let mut file_bytes: Vec<u8> = Vec::new();
-    file.read_to_end(&mut file_bytes);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mimc;
+    use crate::air::evaluation_domain;
+    use crate::field::{FieldElement, Goldilocks, CubicExtension};
+
+    fn load_proof() -> StarkProof {
+        let bytes = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/proof.bin")).unwrap();
+        deserializer::from_bytes(&bytes).unwrap()
+    }
+
+    fn constants() -> Vec<FieldElement> {
+        (0..64u64).map(|i| FieldElement::from(i.pow(7) ^ 42)).collect()
+    }
+
+    fn output() -> FieldElement {
+        mimc(FieldElement::from(3u64), 8192, &constants())
+    }
+
+    fn inputs(output: FieldElement) -> MimcPublicInputs<FieldElement> {
+        MimcPublicInputs::new(FieldElement::from(3u64), 8192, &constants(), output)
+    }
+
+    #[test]
+    fn test_verify_mimc_proof() {
+        assert_eq!(output().to_string(), "95224774355499767951968048714566316597785297695903697235130434363122555476056");
+        assert_eq!(verify_mimc_proof(&inputs(output()), &load_proof(), &ProofOptions::legacy()), Ok(()));
+    }
+
+    #[test]
+    fn test_mimc_domain() {
+        let minus_one = -FieldElement::ONE;
+
+        for log_steps in 1..29 {
+            let num_steps = 1usize << log_steps;
+
+            for log_constants in (0..(log_steps+1)).step_by(3) {
+                let num_constants = 1usize << log_constants;
+                let (g2, constants_root) = evaluation_domain::<FieldElement>(num_steps, num_constants, 8).unwrap();
+                let precision = num_steps * 8;
+
+                // g2 and the constants root have exactly the expected orders
+                assert_eq!(g2.pow((precision / 2) as u64), minus_one);
+                assert_eq!(g2.pow(precision as u64), FieldElement::ONE);
+                assert_eq!(constants_root.pow(num_constants as u64), FieldElement::ONE);
+                if num_constants > 1 {
+                    assert_eq!(constants_root.pow((num_constants / 2) as u64), minus_one);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_mimc_domain_8192_steps() {
+        let (g2, constants_root) = evaluation_domain::<FieldElement>(8192, 64, 8).unwrap();
+
+        assert_eq!(g2.to_string(), "41913712888260089065520476180880993127517355946012995597287997778376518235852");
+        assert_eq!(constants_root.to_string(), "56670364103764250102176604807203318908867195832872336813161821519223575486477");
+        assert_eq!(g2.pow(65536 * 3 / 4).to_string(), "80127877722526290441229381276271393407378829608771736609433200039324583025757");
+    }
+
+    #[test]
+    fn test_mimc_domain_goldilocks() {
+        let (g2, constants_root) = evaluation_domain::<Goldilocks>(8192, 64, 8).unwrap();
+
+        assert_eq!(g2.pow(65536 / 2), -Goldilocks::ONE);
+        assert_eq!(constants_root.pow(32), -Goldilocks::ONE);
+        assert_eq!(CubicExtension::from(g2).pow(65536), CubicExtension::ONE);
+    }
+
+    #[test]
+    fn test_mimc_domain_bad_parameters() {
+        assert!(evaluation_domain::<FieldElement>(0, 1, 8).is_err());
+        assert!(evaluation_domain::<FieldElement>(8192, 0, 8).is_err());
+        assert!(evaluation_domain::<FieldElement>(8000, 64, 8).is_err());
+        assert!(evaluation_domain::<FieldElement>(8192, 48, 8).is_err());
+        assert!(evaluation_domain::<FieldElement>(64, 128, 8).is_err());
+        assert!(evaluation_domain::<FieldElement>(1 << 29, 64, 8).is_err());
+    }
+
+    #[test]
+    fn test_mismatched_options_are_rejected() {
+        let options = ProofOptions { num_queries: 40, ..ProofOptions::legacy() };
+
+        match verify_mimc_proof(&inputs(output()), &load_proof(), &options) {
+            Err(VerifyError::BranchCountMismatch { layer: 0, .. }) => {},
+            res => panic!("unexpected result {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_mismatched_hash_is_rejected() {
+        let options = ProofOptions { hash_fn: HashFunction::Keccak256, ..ProofOptions::legacy() };
 
-    let (proof, _) = deserializer::from_bytes(&file_bytes).expect("couldn't deserialize"); 
-    const LOG_STEPS: usize = 13;
-    let mut constants: Vec<BigInt> = Vec::new();
-    let modulus: BigInt = BigInt::from_str(MODULUS).expect("modulus couldn't be deserialized into bigint");
+        match verify_mimc_proof(&inputs(output()), &load_proof(), &options) {
+            Err(VerifyError::MerkleRootMismatch { .. }) => {},
+            res => panic!("unexpected result {:?}", res),
+        }
+    }
 
-    for i in 0..64 {
-        let constant = BigInt::from(i as u8).pow(BigUint::from(7u8)) ^ BigInt::from(42u8);
-        constants.push(constant);
+    #[test]
+    fn test_legacy_proof_fails_labeled_transcript() {
+        // proof.bin's challenges don't depend on the public inputs, so it only verifies in legacy mode
+        assert!(verify_mimc_proof(&inputs(output()), &load_proof(), &ProofOptions::default()).is_err());
     }
 
-    let mimc_time = Instant::now();
-    let output = mimc(&BigInt::from(3u8), 2usize.pow(LOG_STEPS as u32), &constants, &modulus);
+    #[test]
+    fn test_wrong_output_is_rejected() {
+        let output = output() + FieldElement::ONE;
 
-    println!("took {:?} to compute {} rounds of mimc", mimc_time.elapsed(), 2usize.pow(LOG_STEPS as u32));
-    println!("output is {}", &output);
-    
-    let stark_time = Instant::now();
-    // TODO start measuring for benchmarks here
-    if !verify_mimc_proof(BigInt::from(3u8), 2usize.pow(LOG_STEPS as u32), &constants, output, proof, &modulus) {
-        panic!("could not verify mimc stark proof");
+        match verify_mimc_proof(&inputs(output), &load_proof(), &ProofOptions::legacy()) {
+            Err(VerifyError::BoundaryConstraint { .. }) => {},
+            res => panic!("unexpected result {:?}", res),
+        }
     }
 
-    println!("took {:?} to verify stark proof", stark_time.elapsed());
+    #[test]
+    fn test_tampered_fri_points_are_rejected() {
+        let mut proof = load_proof();
+        proof.fri_proof.points_proof[1] += FieldElement::ONE;
+
+        assert_eq!(verify_mimc_proof(&inputs(output()), &proof, &ProofOptions::legacy()), Err(VerifyError::FinalLayerRootMismatch));
+    }
+
+    #[test]
+    fn test_final_layer_too_few_points() {
+        // 16 points with every 8th excluded leave 14, one short of what degree 14 takes
+        let points = vec![Goldilocks::ONE; 16];
+        let leaves: Vec<Value> = points.iter().map(|x| x.to_bytes_be()).collect();
+        let root = merkle_tree::merkle_root::<Blake2s256>(&leaves, TranscriptMode::Labeled, 4);
+        let proof = FRIProof { merkle_proofs: vec![], points_proof: points };
+        let mut transcript = Transcript::<Blake2s256>::new(TranscriptMode::Labeled, b"test");
+
+        let res = verify_low_degree_proof::<Goldilocks, Goldilocks, Blake2s256>(&mut transcript, FirstLayer::Committed(&root), Goldilocks::get_root_of_unity(16), &proof, 15, Some(8), &ProofOptions::default());
+        assert_eq!(res, Err(VerifyError::FinalLayerTooFewPoints { needed: 15, available: 14 }));
+    }
+
+    #[test]
+    fn test_tampered_trace_is_rejected() {
+        let mut proof = load_proof();
+        proof.merkle_branches.branches[0].value[0] ^= 1;
+
+        match verify_mimc_proof(&inputs(output()), &proof, &ProofOptions::legacy()) {
+            Err(VerifyError::MerkleRootMismatch { layer: 0, .. }) => {},
+            res => panic!("unexpected result {:?}", res),
+        }
+    }
 }
-*/
@@ -1,12 +1,23 @@
-use rustfft::num_traits::Pow;
-use blake2::{Blake2s, Digest};
-use std::fs::File;
-use std::io::Read;
-use crate::utils::{as_u32_le};
+use crate::error::VerifyError;
+use crate::hash::Hasher;
+use crate::options::TranscriptMode;
+use crate::utils::par_map;
 
 pub type Value = Vec<u8>;
 pub type MerkleDigest = [u8; 32];
 
+// new trees prefix what they hash with the level, so that a pair of leaves can't be passed
+// off as a pair of digests (the legacy format of the python prover hashes them alike)
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+fn hash_pair<H: Hasher>(mode: TranscriptMode, prefix: u8, left: &[u8], right: &[u8]) -> MerkleDigest {
+    match mode {
+        TranscriptMode::Legacy => H::hash_children(left, right),
+        TranscriptMode::Labeled => H::hash_leaf(&[&[prefix][..], left, right].concat()),
+    }
+}
+
 #[derive(Default)]
 pub struct ProofBranch {
     pub witnesses: Vec<MerkleDigest>,
@@ -22,148 +33,142 @@ pub struct MultiProof {
 
 // TODO update the package version of this code
 impl MultiProof {
-    pub fn verify(&self, indices: &[u32], rt: Option<MerkleDigest>) -> Option<Vec<Value>> {
+    /// Verifies the branches of `indices` in a tree over 2^depth values with leaves grouped
+    /// by `group`
+    pub fn verify<H: Hasher>(&self, indices: &[u32], rt: Option<MerkleDigest>, layer: usize, depth: usize, mode: TranscriptMode, group: usize) -> Result<Vec<Value>, VerifyError> {
        let mut res: Vec<Value> = Default::default();
 
-       assert!(self.branches.len() == indices.len(), format!("branches len {} != indices len {}", self.branches.len(), indices.len()));
+       if self.branches.len() != indices.len() {
+           return Err(VerifyError::BranchCountMismatch { layer, expected: indices.len(), actual: self.branches.len() });
+       }
+
+       let root = rt.unwrap_or(self.root);
 
-       let root = match rt {
-           Some(r) => r.clone(),
-           None => self.root.clone()
-       };
+       let branches: Vec<(&ProofBranch, &u32)> = self.branches.iter().zip(indices.iter()).collect();
+       let values = par_map(&branches, |(branch, i)| branch.verify::<H>(&root, **i, depth, mode, group));
 
-       for (branch, i) in self.branches.iter().zip(indices.iter()) {
-            if let Some(value) = branch.verify(&root, *i)  {
+       for (value, i) in values.into_iter().zip(indices.iter()) {
+            if let Some(value) = value  {
                 res.push(value);
             } else {
-                return None;
+                return Err(VerifyError::MerkleRootMismatch { layer, index: *i });
             }
        }
 
-        Some(res) 
+        Ok(res) 
     }
+}
 
-    pub fn deserialize(mut f: &File) -> Self {
-        let mut branches: MultiProof = Default::default();
-        let mut num_branches_bytes = [0u8; 4];
-		let mut num_branches: u32 = 0;
-
-
-		f.read_exact(&mut num_branches_bytes).unwrap();
-		num_branches = as_u32_le(&num_branches_bytes);
-
-        let mut branches: Vec<ProofBranch> = Default::default();
-
-        for branch in 0..(num_branches as usize) {
-            let mut witnesses_size_bytes = [0u8; 4];
-            let mut value_size_bytes = [0u8; 4];
-
-            let mut witnesses: Vec<MerkleDigest> = Default::default();
-
-            f.read_exact(&mut value_size_bytes);
-            let value_size = as_u32_le(&mut value_size_bytes);
-
-            let mut value: Value = vec![0u8; value_size as usize];
-            let mut sibling_value: Value = vec![0u8; value_size as usize];
-
-            f.read_exact(&mut value[0..value_size as usize]).unwrap();
-            f.read_exact(&mut sibling_value[0..value_size as usize]).unwrap();
-
-            f.read_exact(&mut witnesses_size_bytes).unwrap();
-
-            let witnesses_size = as_u32_le(&mut witnesses_size_bytes);
-            assert!(witnesses_size % 32 == 0, format!("witnesses should all be 32 bytes: {}", witnesses_size));
+/// The tree the prover commits `values` with. The leaves are permuted so that the `group`
+/// values x, x + L/group, ... x + (group - 1)L/group sit next to each other, the positions
+/// a FRI query with a folding factor of `group` opens, and the lowest level hashes pairs of
+/// raw values. `values.len()` must be a power of two of at least 4, and `group` a power of
+/// two (capped at `values.len()`).
+pub struct MerkleTree {
+    // nodes[1] is the root, the permuted leaves are nodes[L..2L]
+    nodes: Vec<Value>,
+    group: usize,
+}
 
-            let num_witnesses = witnesses_size / 32;
+impl MerkleTree {
+    pub fn new<H: Hasher>(values: &[Value], mode: TranscriptMode, group: usize) -> Self {
+        let group = group.min(values.len());
+        let stride = values.len() / group;
+        let mut nodes: Vec<Value> = vec![Vec::new(); values.len()];
 
-            for i in 0..(num_witnesses as usize) {
-                let mut witness = [0u8; 32];
-                f.read_exact(&mut witness);
-                witnesses.push(witness);
+        for i in 0..stride {
+            for j in 0..group {
+                nodes.push(values[i + stride * j].clone());
             }
+        }
 
-            branches.push(ProofBranch {
-                witnesses: witnesses,
-                sibling_value: sibling_value,
-                value: value
-            });
+        for i in (1..values.len()).rev() {
+            let prefix = if i >= values.len() / 2 { LEAF_PREFIX } else { NODE_PREFIX };
+            nodes[i] = hash_pair::<H>(mode, prefix, &nodes[i*2], &nodes[i*2 + 1]).to_vec();
         }
 
-        let multiproof = MultiProof {
-            branches: branches,
-            root: Default::default()
-        };
+        MerkleTree { nodes, group }
+    }
 
-        multiproof
+    pub fn root(&self) -> MerkleDigest {
+        let mut root: MerkleDigest = [0u8; 32];
+        root.clone_from_slice(&self.nodes[1]);
+        root
     }
-}
 
-impl ProofBranch {
-    fn permute_4_indices(indices: &Vec<u32>, L: u32) -> Vec<u32> {
-        let mut res: Vec<u32> = Vec::new();
-        let ld4 = L / 4;
-        indices.iter().map(|idx| {
-            res.push(Self::permute_4_index(*idx, ld4));
-        });
+    /// The branch of the value at position `a`, as `ProofBranch::verify` expects it
+    pub fn branch(&self, a: u32) -> ProofBranch {
+        let leaves = self.nodes.len() / 2;
+        let mut index = leaves + ProofBranch::permute_index(a, leaves as u32, self.group as u32) as usize;
+
+        let value = self.nodes[index].clone();
+        let sibling_value = self.nodes[index ^ 1].clone();
+        let mut witnesses: Vec<MerkleDigest> = Vec::new();
+
+        index /= 2;
+        while index > 1 {
+            let mut witness: MerkleDigest = [0u8; 32];
+            witness.clone_from_slice(&self.nodes[index ^ 1]);
+            witnesses.push(witness);
+            index /= 2;
+        }
 
-        res
+        ProofBranch { witnesses, sibling_value, value }
     }
 
-    fn permute_4_index(x: u32, L: u32) -> u32 {
-        let ld4 = L / 4;
-        let res = (x / ld4) + 4 * (x % ld4);
-        res
+    pub fn multi_branch(&self, indices: &[u32]) -> MultiProof {
+        MultiProof {
+            branches: indices.iter().map(|a| self.branch(*a)).collect(),
+            root: self.root(),
+        }
     }
+}
 
-    // expect the witnesses to be sorted in reverse
-    pub fn verify(&self, root: &MerkleDigest, a: u32) -> Option<Value> {
-        let idx = Self::permute_4_index(a, 2u32.pow((self.witnesses.len()+1) as u32));
+/// Root of the `MerkleTree` committing to `values`
+pub fn merkle_root<H: Hasher>(values: &[Value], mode: TranscriptMode, group: usize) -> MerkleDigest {
+    MerkleTree::new::<H>(values, mode, group).root()
+}
 
-        let mut res = vec![0u8; self.value.len()];
-        res[0..self.value.len()].clone_from_slice(&self.value[..]);
+impl ProofBranch {
+    // where the value at position x of `l` sits among the leaves, see `MerkleTree`
+    fn permute_index(x: u32, l: u32, group: u32) -> u32 {
+        let group = group.min(l);
+        let stride = l / group;
+        (x / stride) + group * (x % stride)
+    }
 
-        let mut tree_index = 2usize.pow((self.witnesses.len() + 2) as u32) + idx as usize;
+    /// The value at position `a` of a tree over 2^depth values with leaves grouped by `group`,
+    /// if the branch leads to `root`. The witnesses are sorted from the bottom of the tree up.
+    pub fn verify<H: Hasher>(&self, root: &MerkleDigest, a: u32, depth: usize, mode: TranscriptMode, group: usize) -> Option<Value> {
+        // the leaf and its sibling form the bottom level, the witnesses the ones above it:
+        // a branch of any other length would open a node of another level as a leaf
+        if !(2..=31).contains(&depth) || self.witnesses.len() + 1 != depth || a >= 1 << depth {
+            return None;
+        }
 
-        let mut h = Blake2s::default();
+        let idx = Self::permute_index(a, 1 << depth, group as u32);
 
-        if tree_index % 2 != 0 {
-            let b: &[u8] = &res[0..self.value.len()];
-            let o: &[u8] = &[&self.sibling_value[..], &b].concat();
-            h.input(o);
-            res[0..32].clone_from_slice(&h.result()[0..32]);
+        let mut tree_index = (1u64 << depth) + idx as u64;
 
+        let mut res: MerkleDigest = if tree_index & 1 == 1 {
+            hash_pair::<H>(mode, LEAF_PREFIX, &self.sibling_value, &self.value)
         } else {
-            let b: &[u8] = &res[0..self.value.len()];
-            let o: &[u8] = &[&b, &self.sibling_value[..]].concat();
-
-            h.input(o);
-            res[0..32].clone_from_slice(&h.result()[0..32]);
-        }
-
-        tree_index = tree_index / 2;
-
-        for (i, witness) in self.witnesses.iter().enumerate() {
-            let mut hasher = Blake2s::default();
+            hash_pair::<H>(mode, LEAF_PREFIX, &self.value, &self.sibling_value)
+        };
 
-            if tree_index % 2 != 0 {
-              let b: &[u8] = &res[0..32];
-              let o: &[u8] = &[&witness[..], &b].concat();
-              hasher = Default::default();
-              hasher.input(o);
+        tree_index /= 2;
 
-              res[0..32].clone_from_slice(&hasher.result()[0..32]);
+        for witness in self.witnesses.iter() {
+            if tree_index & 1 == 1 {
+                res = hash_pair::<H>(mode, NODE_PREFIX, witness, &res);
             } else {
-              let b: &[u8] = &res[0..32];
-              let o: &[u8] = &[&b, &witness[..]].concat();
-              hasher.input(o);
-              res[0..32].clone_from_slice(&hasher.result()[0..32]);
+                res = hash_pair::<H>(mode, NODE_PREFIX, &res, witness);
             }
 
-            tree_index = tree_index / 2;
+            tree_index /= 2;
         }
 
-        assert!(&res[0..32] == root, format!("values didn't match up {} != {}", hex::encode(&res[0..32]), hex::encode(root)));
-        if &res[0..32] == root {
+        if &res == root {
             Some(self.value.clone())
         } else {
             None
@@ -173,8 +178,9 @@ impl ProofBranch {
 
 #[cfg(test)]
 mod tests {
-    use blake2::{Blake2s, Digest};
     use super::*;
+    use crate::hash::{Blake2s256, RescueLike, PoseidonLike};
+    use crate::field::{FieldElement, Goldilocks};
 
     fn convert(a: &[u8]) -> [u8; 32] {
         let mut b = [0u8; 32];
@@ -203,11 +209,114 @@ mod tests {
 
         let root: MerkleDigest = convert(&hex::decode("f13a4bfaa28c22df47a4e0e89a54736b0a9bedc9727bbc3cc8a0e4237eb59ad9").unwrap());
 
+        // the vector predates leaf pairs: the first witness is the sibling leaf, and
+        // position 5845 is the one that permutes to index 6997 in the tree
         let proof_branch = ProofBranch {
-            witnesses,
-            value
+            sibling_value: witnesses[0].to_vec(),
+            witnesses: witnesses[1..].to_vec(),
+            value: value.to_vec()
+        };
+
+        assert!(proof_branch.verify::<Blake2s256>(&root, 5845, 14, TranscriptMode::Legacy, 4).is_some(), "proof was invalid");
+        assert!(proof_branch.verify::<Blake2s256>(&root, idx, 14, TranscriptMode::Legacy, 4).is_none(), "proof verified at the wrong position");
+        assert!(proof_branch.verify::<Blake2s256>(&root, 5845, 15, TranscriptMode::Legacy, 4).is_none(), "proof verified at the wrong depth");
+        assert!(proof_branch.verify::<Blake2s256>(&root, 5845, 14, TranscriptMode::Labeled, 4).is_none(), "legacy proof verified without the prefixes");
+    }
+
+    #[test]
+    fn test_multiproof_rejects_bad_branch() {
+        let mut sibling = [0u8; 32];
+        sibling[0] = 1;
+        let branch = ProofBranch {
+            witnesses: vec![[2u8; 32]],
+            sibling_value: sibling.to_vec(),
+            value: vec![3u8; 32]
         };
 
-        assert!(proof_branch.verify(&root, root).is_some(), "proof was invalid");
+        let multiproof = MultiProof { branches: vec![branch], root: [0u8; 32] };
+
+        assert_eq!(multiproof.verify::<Blake2s256>(&[1], None, 3, 2, TranscriptMode::Labeled, 4), Err(VerifyError::MerkleRootMismatch { layer: 3, index: 1 }));
+        assert_eq!(multiproof.verify::<Blake2s256>(&[1, 2], None, 3, 2, TranscriptMode::Labeled, 4), Err(VerifyError::BranchCountMismatch { layer: 3, expected: 2, actual: 1 }));
+    }
+
+    #[test]
+    fn test_branch_without_witnesses_is_rejected() {
+        let branch = ProofBranch {
+            witnesses: vec![],
+            sibling_value: vec![],
+            value: vec![]
+        };
+
+        assert!(branch.verify::<Blake2s256>(&[0u8; 32], 0, 1, TranscriptMode::Labeled, 4).is_none());
+    }
+
+    #[test]
+    fn test_node_is_not_a_leaf() {
+        let values: Vec<Value> = (0..16u8).map(|i| vec![i; 32]).collect();
+
+        for mode in [TranscriptMode::Legacy, TranscriptMode::Labeled] {
+            let tree = MerkleTree::new::<Blake2s256>(&values, mode, 4);
+            let root = tree.root();
+            let branch = tree.branch(5);
+            assert_eq!(branch.verify::<Blake2s256>(&root, 5, 4, mode, 4), Some(values[5].clone()));
+
+            // open the parent of the leaf pair as if it was a leaf of a tree half the size
+            let parent = (16 + ProofBranch::permute_index(5, 16, 4) as usize) / 2;
+            let forged = ProofBranch {
+                witnesses: branch.witnesses[1..].to_vec(),
+                sibling_value: tree.nodes[parent ^ 1].clone(),
+                value: tree.nodes[parent].clone(),
+            };
+
+            assert!((0..16).all(|a| forged.verify::<Blake2s256>(&root, a, 4, mode, 4).is_none()));
+
+            // without the prefixes only the expected depth tells the two apart
+            let opens = (0..8).any(|a| forged.verify::<Blake2s256>(&root, a, 3, mode, 4).is_some());
+            assert_eq!(opens, mode == TranscriptMode::Legacy);
+        }
+    }
+
+    #[test]
+    fn test_leaf_groups() {
+        let values: Vec<Value> = (0..64u8).map(|i| vec![i; 32]).collect();
+
+        for group in [2, 4, 8, 16] {
+            let tree = MerkleTree::new::<Blake2s256>(&values, TranscriptMode::Labeled, group);
+            let root = tree.root();
+            let stride = 64 / group as u32;
+            let log_group = group.trailing_zeros() as usize;
+
+            // the positions of a query share the path above their group
+            let branches: Vec<ProofBranch> = (0..group as u32).map(|j| tree.branch(3 + stride * j)).collect();
+            for (j, branch) in branches.iter().enumerate() {
+                assert_eq!(branch.witnesses[(log_group - 1)..], branches[0].witnesses[(log_group - 1)..]);
+                assert_eq!(branch.verify::<Blake2s256>(&root, 3 + stride * j as u32, 6, TranscriptMode::Labeled, group), Some(values[3 + stride as usize * j].clone()));
+            }
+        }
+
+        // a group can't be larger than the tree
+        let tree = MerkleTree::new::<Blake2s256>(&values[..4], TranscriptMode::Labeled, 16);
+        assert_eq!(tree.branch(1).verify::<Blake2s256>(&tree.root(), 1, 2, TranscriptMode::Labeled, 16), Some(values[1].clone()));
+    }
+
+    fn check_algebraic_tree<H: Hasher>() {
+        let values: Vec<Value> = (0..16u8).map(|i| vec![i; 32]).collect();
+        let tree = MerkleTree::new::<H>(&values, TranscriptMode::Labeled, 4);
+        let root = tree.root();
+
+        for a in [0, 5, 10, 15].iter().cloned() {
+            let branch = tree.branch(a);
+            assert_eq!(branch.verify::<H>(&root, a, 4, TranscriptMode::Labeled, 4), Some(values[a as usize].clone()));
+            assert!(branch.verify::<H>(&root, (a + 1) % 16, 4, TranscriptMode::Labeled, 4).is_none());
+            assert!(branch.verify::<Blake2s256>(&root, a, 4, TranscriptMode::Labeled, 4).is_none());
+        }
+    }
+
+    #[test]
+    fn test_algebraic_hash_trees() {
+        check_algebraic_tree::<RescueLike<Goldilocks>>();
+        check_algebraic_tree::<PoseidonLike<Goldilocks>>();
+        check_algebraic_tree::<RescueLike<FieldElement>>();
+        check_algebraic_tree::<PoseidonLike<FieldElement>>();
     }
 }
@@ -0,0 +1,172 @@
+use crate::error::VerifyError;
+use crate::utils::is_power_of_2;
+
+/// Hash function used for the Merkle commitments and to derive the verifier's challenges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashFunction {
+    /// used by the python prover
+    Blake2s,
+    Blake2b,
+    Sha256,
+    /// for proofs verified on the EVM
+    Keccak256,
+    Blake3,
+    /// arithmetization friendly, over the proof's base field. They follow the round structure
+    /// of Rescue-Prime and Poseidon but not their reference parameters
+    RescueLike,
+    PoseidonLike,
+}
+
+impl HashFunction {
+    // identifiers in proof headers
+    pub(crate) fn id(&self) -> u32 {
+        match self {
+            HashFunction::Blake2s => 1,
+            HashFunction::Blake2b => 2,
+            HashFunction::Sha256 => 3,
+            HashFunction::Keccak256 => 4,
+            HashFunction::Blake3 => 5,
+            HashFunction::RescueLike => 6,
+            HashFunction::PoseidonLike => 7,
+        }
+    }
+
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        match id {
+            1 => Some(HashFunction::Blake2s),
+            2 => Some(HashFunction::Blake2b),
+            3 => Some(HashFunction::Sha256),
+            4 => Some(HashFunction::Keccak256),
+            5 => Some(HashFunction::Blake3),
+            6 => Some(HashFunction::RescueLike),
+            7 => Some(HashFunction::PoseidonLike),
+            _ => None,
+        }
+    }
+}
+
+/// How the verifier's challenges are derived from the commitments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptMode {
+    /// as the python prover does it, without binding the public inputs
+    Legacy,
+    /// a labeled transcript absorbing the public inputs and every commitment, with the
+    /// Merkle tree levels hashed apart
+    Labeled,
+}
+
+impl TranscriptMode {
+    pub(crate) fn id(&self) -> u32 {
+        match self {
+            TranscriptMode::Legacy => 0,
+            TranscriptMode::Labeled => 1,
+        }
+    }
+
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        match id {
+            0 => Some(TranscriptMode::Legacy),
+            1 => Some(TranscriptMode::Labeled),
+            _ => None,
+        }
+    }
+}
+
+/// Parameters a proof was generated with. Both sides need to agree on them: they
+/// determine the evaluation domain, how many positions are queried and how FRI folds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofOptions {
+    /// ratio between the size of the evaluation domain and the trace length
+    pub extension_factor: usize,
+    /// number of positions the constraints are spot-checked at
+    pub num_queries: usize,
+    /// number of positions queried in each FRI layer
+    pub num_fri_queries: usize,
+    /// by how much each FRI layer reduces the domain
+    pub fri_folding_factor: usize,
+    pub hash_fn: HashFunction,
+    pub transcript: TranscriptMode,
+}
+
+impl Default for ProofOptions {
+    // the parameters of the python mimc_stark prover, with a labeled transcript
+    fn default() -> Self {
+        ProofOptions {
+            extension_factor: 8,
+            num_queries: 80,
+            num_fri_queries: 40,
+            fri_folding_factor: 4,
+            hash_fn: HashFunction::Blake2s,
+            transcript: TranscriptMode::Labeled,
+        }
+    }
+}
+
+impl ProofOptions {
+    /// The options of the python mimc_stark prover, proof.bin verifies with them. Its
+    /// challenges don't depend on the public inputs.
+    pub fn legacy() -> Self {
+        ProofOptions { transcript: TranscriptMode::Legacy, ..ProofOptions::default() }
+    }
+
+    /// Options for new proofs, whose challenges come from a labeled transcript
+    pub fn new(extension_factor: usize, num_queries: usize, num_fri_queries: usize, fri_folding_factor: usize, hash_fn: HashFunction) -> Result<Self, VerifyError> {
+        let options = ProofOptions {
+            extension_factor,
+            num_queries,
+            num_fri_queries,
+            fri_folding_factor,
+            hash_fn,
+            transcript: TranscriptMode::Labeled,
+        };
+
+        options.validate()?;
+        Ok(options)
+    }
+
+    // the parameters as absorbed into the transcript
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        [self.extension_factor, self.num_queries, self.num_fri_queries, self.fri_folding_factor].iter()
+            .flat_map(|x| (*x as u64).to_be_bytes().to_vec())
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<(), VerifyError> {
+        // the composition polynomial has degree 2 * num_steps, a smaller domain couldn't tell it apart from a random function
+        if !is_power_of_2(self.extension_factor) || self.extension_factor < 4 {
+            return Err(VerifyError::BadParameters("extension factor must be a power of 2 of at least 4"));
+        }
+
+        if self.num_queries == 0 || self.num_fri_queries == 0 {
+            return Err(VerifyError::BadParameters("at least one query is required"));
+        }
+
+        if ![2, 4, 8, 16].contains(&self.fri_folding_factor) {
+            return Err(VerifyError::BadParameters("the FRI folding factor must be 2, 4, 8 or 16"));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(ProofOptions::default().validate().is_ok());
+        assert!(ProofOptions::legacy().validate().is_ok());
+        assert_eq!(ProofOptions::default().transcript, TranscriptMode::Labeled);
+        assert!(ProofOptions::new(16, 40, 20, 4, HashFunction::Blake2s).is_ok());
+        assert!(ProofOptions::new(8, 80, 40, 4, HashFunction::Keccak256).is_ok());
+        assert!(ProofOptions::new(8, 80, 40, 4, HashFunction::RescueLike).is_ok());
+        assert!(ProofOptions::new(6, 80, 40, 4, HashFunction::Blake2s).is_err());
+        assert!(ProofOptions::new(2, 80, 40, 4, HashFunction::Blake2s).is_err());
+        assert!(ProofOptions::new(8, 0, 40, 4, HashFunction::Blake2s).is_err());
+        assert!(ProofOptions::new(8, 80, 40, 3, HashFunction::Blake2s).is_err());
+        assert!(ProofOptions::new(8, 80, 40, 2, HashFunction::Blake2s).is_ok());
+        assert!(ProofOptions::new(8, 80, 40, 16, HashFunction::Blake2s).is_ok());
+        assert!(ProofOptions::new(8, 80, 40, 32, HashFunction::Blake2s).is_err());
+    }
+}
@@ -1,24 +1,38 @@
-use std::mem::{size_of};
+use crate::field::{Field, FieldElement};
 use crate::merkle_tree::{MultiProof, MerkleDigest};
-use crate::utils::{as_u32_le};
-use std::fs::{File};
-use std::io::Read;
 
-pub struct FRIProof {
+pub struct FRIProof<F: Field = FieldElement> {
     pub merkle_proofs: Vec<LDPMerkleProof>,
-    pub points_proof: LDPPointsProof,
+    pub points_proof: LDPPointsProof<F>,
 }
 
-pub type LDPPointsProof = Vec<u32>;
+/// The evaluations of the last FRI layer, sent in the clear
+pub type LDPPointsProof<F = FieldElement> = Vec<F>;
 
-pub struct StarkProof {
+pub struct StarkProof<F: Field = FieldElement> {
     pub merkle_root: MerkleDigest,
     pub l_merkle_root: MerkleDigest,
-    pub fri_proof: FRIProof,
+    pub fri_proof: FRIProof<F>,
     pub merkle_branches: MultiProof,
     pub linear_comb_branches: MultiProof,
 }
 
+/// A proof of the DEEP variant of the protocol. The verifier checks the constraints once,
+/// at an out-of-domain point z, and FRI tests the DEEP composition of the trace and the
+/// constraint composition, whose values it computes from the openings of both.
+pub struct DeepProof<F: Field = FieldElement> {
+    pub trace_root: MerkleDigest,
+    pub composition_root: MerkleDigest,
+    /// the registers at z, then at g * z
+    pub ood_frame: Vec<F>,
+    /// the constraint composition at z
+    pub ood_composition: F,
+    pub fri_proof: FRIProof<F>,
+    /// openings at the positions the first FRI layer queries
+    pub trace_branches: MultiProof,
+    pub composition_branches: MultiProof,
+}
+
 #[derive(Default)]
 pub struct LDPMerkleProof {
     pub root2: [u8; 32],
@@ -0,0 +1,637 @@
+use crate::error::VerifyError;
+use crate::fft::{fft_in_place, fft_inv};
+use crate::field::Field;
+use crate::hash::{Hasher, AlgebraicHashField, Blake2s256, Blake2b256, Sha256, Keccak256, Blake3, RescueLike, PoseidonLike};
+use crate::merkle_tree::{MerkleTree, MultiProof, Value};
+use crate::options::{HashFunction, ProofOptions};
+use crate::proof::{DeepProof, FRIProof, LDPMerkleProof, StarkProof};
+use crate::transcript::{Transcript, FRI_POINT};
+use crate::utils::{multi_inv, multi_interp, eval_poly_at, mul_polys, div_by_sparse, div_by_monic};
+use crate::air::{evaluation_domain, Air, AirContext, MimcAir, MimcPublicInputs};
+use crate::air_transcript;
+use crate::deep::{deep_transcript, num_composition_coefficients};
+
+// 1, x, x^2, ... x^(n-1)
+fn powers<F: Field>(x: F, n: usize) -> Vec<F> {
+    let mut output: Vec<F> = vec![F::ONE];
+
+    for _ in 1..n {
+        let next = output[output.len()-1] * x;
+        output.push(next);
+    }
+
+    output
+}
+
+fn to_leaves<F: Field>(values: &[F]) -> Vec<Value> {
+    values.iter().map(|x| x.to_bytes_be()).collect()
+}
+
+/// Proves that `num_steps` rounds of MiMC with `round_constants` map `inp` to
+/// `utils::mimc(inp, num_steps, round_constants)`, the statement `verify_mimc_proof`
+/// checks. The proof is laid out like the python prover's, `serializer::to_bytes` turns
+/// it into the format `deserializer::from_bytes` reads.
+pub fn prove_mimc<F: AlgebraicHashField, E: Field + From<F>>(inp: F, num_steps: usize, round_constants: &[F], options: &ProofOptions) -> Result<StarkProof<E>, VerifyError> {
+    // the trace is only computed for valid parameters
+    evaluation_domain::<F>(num_steps, round_constants.len(), options.extension_factor)?;
+
+    let mut air = MimcAir::new(MimcPublicInputs::new(inp, num_steps, round_constants, F::ZERO));
+    let trace = air.trace();
+    air.inputs.output = trace[num_steps - 1];
+
+    prove(&air, &[trace], options)
+}
+
+/// Proves that `trace`, given as one vector of values per register, satisfies the
+/// constraints of `air`. The counterpart of `verify`.
+pub fn prove<A: Air, E: Field + From<A::BaseField>>(air: &A, trace: &[Vec<A::BaseField>], options: &ProofOptions) -> Result<StarkProof<E>, VerifyError> {
+    options.validate()?;
+
+    if trace.len() != air.trace_width() || trace.iter().any(|column| column.len() != air.trace_length()) {
+        return Err(VerifyError::BadParameters("the trace doesn't have the shape of the Air"));
+    }
+
+    match options.hash_fn {
+        HashFunction::Blake2s => prove_with::<A, E, Blake2s256>(air, trace, options),
+        HashFunction::Blake2b => prove_with::<A, E, Blake2b256>(air, trace, options),
+        HashFunction::Sha256 => prove_with::<A, E, Sha256>(air, trace, options),
+        HashFunction::Keccak256 => prove_with::<A, E, Keccak256>(air, trace, options),
+        HashFunction::Blake3 => prove_with::<A, E, Blake3>(air, trace, options),
+        HashFunction::RescueLike => prove_with::<A, E, RescueLike<A::BaseField>>(air, trace, options),
+        HashFunction::PoseidonLike => prove_with::<A, E, PoseidonLike<A::BaseField>>(air, trace, options),
+    }
+}
+
+fn prove_with<A: Air, E: Field + From<A::BaseField>, H: Hasher>(air: &A, trace: &[Vec<A::BaseField>], options: &ProofOptions) -> Result<StarkProof<E>, VerifyError> {
+    let context = AirContext::new(air, options)?;
+
+    let num_steps = context.trace_length;
+    let extension_factor = context.extension_factor;
+    let precision = context.precision;
+    let g2 = context.root_of_unity;
+
+    let xs = powers(g2, precision);
+    let last_step_position = context.last_step_position;
+
+    let width = context.trace_width;
+
+    // each P(x) interpolates a register over the steps and is evaluated over the extended domain
+    let p_evaluations: Vec<Vec<A::BaseField>> = trace.iter().map(|column| {
+        let mut evaluations = fft_inv(column, &xs[extension_factor]);
+        evaluations.resize(precision, A::BaseField::ZERO);
+        fft_in_place(&mut evaluations, &g2);
+        evaluations
+    }).collect();
+
+    // a periodic column of length n repeats every n steps, so it only has to be evaluated
+    // over a subgroup of n * extension_factor points
+    let periodic_extensions: Vec<Vec<A::BaseField>> = context.periodic_columns.iter().map(|column| column.extend(g2, extension_factor)).collect();
+
+    // D(x) = C(P(x)) / Z(x) with Z(x) = (x^steps - 1) / (x - last_step_position). Z vanishes
+    // at the multiples of extension_factor, which are never queried
+    let z_num_evaluations: Vec<A::BaseField> = (0..precision).map(|i| xs[(i * num_steps) % precision] - A::BaseField::ONE).collect();
+    let z_num_inv = multi_inv(&z_num_evaluations);
+
+    let d_evaluations: Vec<Vec<A::BaseField>> = (0..precision).map(|i| {
+        let periodic_values: Vec<A::BaseField> = periodic_extensions.iter().map(|e| e[i % e.len()]).collect();
+        let current: Vec<A::BaseField> = p_evaluations.iter().map(|p| p[i]).collect();
+        let next: Vec<A::BaseField> = p_evaluations.iter().map(|p| p[(i + extension_factor) % precision]).collect();
+
+        let mut c_of_p = vec![A::BaseField::ZERO; context.num_constraints];
+        air.evaluate_transition(&current, &next, &periodic_values, &mut c_of_p);
+
+        c_of_p.iter().map(|c| *c * (xs[i] - last_step_position) * z_num_inv[i]).collect()
+    }).collect();
+
+    // B(x) = (P(x) - I(x)) / Q(x), where I interpolates the assertions on the register and Q
+    // vanishes at their steps
+    let b_evaluations: Vec<Vec<A::BaseField>> = (0..width).map(|r| {
+        let inv_z2_evaluations = multi_inv(&xs.iter().map(|x| eval_poly_at(&context.boundary_zerofiers[r], *x)).collect::<Vec<A::BaseField>>());
+
+        (0..precision).map(|i| {
+            (p_evaluations[r][i] - eval_poly_at(&context.boundary_interpolants[r], xs[i])) * inv_z2_evaluations[i]
+        }).collect()
+    }).collect();
+
+    // B(x) = (P(x) - value) / (x^steps - offset) for each periodic assertion
+    let periodic_b_evaluations: Vec<Vec<A::BaseField>> = context.periodic_boundaries.iter().map(|boundary| {
+        let inv_zerofier_evaluations = multi_inv(&xs.iter().map(|x| boundary.zerofier(*x)).collect::<Vec<A::BaseField>>());
+
+        (0..precision).map(|i| {
+            (p_evaluations[boundary.register][i] - boundary.value) * inv_zerofier_evaluations[i]
+        }).collect()
+    }).collect();
+
+    let leaves: Vec<Value> = (0..precision).map(|i| {
+        let mut leaf: Value = Vec::new();
+        for p in &p_evaluations {
+            leaf.extend_from_slice(&p[i].to_bytes_be());
+        }
+        for d in &d_evaluations[i] {
+            leaf.extend_from_slice(&d.to_bytes_be());
+        }
+        for b in b_evaluations.iter().chain(&periodic_b_evaluations) {
+            leaf.extend_from_slice(&b[i].to_bytes_be());
+        }
+        leaf
+    }).collect();
+    let mtree = MerkleTree::new::<H>(&leaves, options.transcript, options.fri_folding_factor);
+
+    let mut transcript = air_transcript::<A, H>(air, options);
+
+    transcript.absorb_commitment(b"trace", &mtree.root());
+    let k: Vec<E> = (0..4 * width).map(|_| transcript.challenge_element(b"constraint-coefficient")).collect();
+    let d_coefficients: Vec<E> = (1..context.num_constraints).map(|_| transcript.challenge_element(b"constraint-coefficient")).collect();
+    let periodic_coefficients: Vec<E> = (0..2 * context.periodic_boundaries.len()).map(|_| transcript.challenge_element(b"constraint-coefficient")).collect();
+
+    let adjustment = context.degree_bound - num_steps;
+
+    let l_evaluations: Vec<E> = (0..precision).map(|i| {
+        // x^adjustment at the i-th position of the domain
+        let x_to_the_adjustment = E::from(xs[(i * adjustment) % precision]);
+
+        let mut l = E::from(d_evaluations[i][0]);
+        for (r, k) in k.chunks(4).enumerate() {
+            let (p_of_x, b_of_x) = (E::from(p_evaluations[r][i]), E::from(b_evaluations[r][i]));
+            l += k[0] * p_of_x + k[1] * p_of_x * x_to_the_adjustment + k[2] * b_of_x + k[3] * b_of_x * x_to_the_adjustment;
+        }
+        for (c, d) in d_coefficients.iter().zip(&d_evaluations[i][1..]) {
+            l += *c * E::from(*d);
+        }
+        for (k, b) in periodic_coefficients.chunks(2).zip(&periodic_b_evaluations) {
+            let b_of_x = E::from(b[i]);
+            l += k[0] * b_of_x + k[1] * b_of_x * x_to_the_adjustment;
+        }
+        l
+    }).collect();
+    let l_mtree = MerkleTree::new::<H>(&to_leaves(&l_evaluations), options.transcript, options.fri_folding_factor);
+
+    transcript.absorb_commitment(b"linear-combination", &l_mtree.root());
+    let positions = transcript.challenge_indices(b"positions", options.num_queries, precision as u32, Some(extension_factor as u32));
+
+    let mut augmented_positions: Vec<u32> = Vec::new();
+
+    for p in &positions {
+        augmented_positions.push(*p);
+        augmented_positions.push((*p + extension_factor as u32) % precision as u32);
+    }
+
+    let (fri_proof, _) = prove_low_degree::<A::BaseField, E, H>(&mut transcript, l_evaluations, Some(&l_mtree), g2, context.degree_bound, Some(extension_factor as u32), options);
+
+    Ok(StarkProof {
+        merkle_root: mtree.root(),
+        l_merkle_root: l_mtree.root(),
+        merkle_branches: mtree.multi_branch(&augmented_positions),
+        linear_comb_branches: l_mtree.multi_branch(&positions),
+        fri_proof,
+    })
+}
+
+/// Proves like `prove`, with the DEEP variant of the protocol `deep::verify_deep` checks
+pub fn prove_deep<A: Air, E: Field + From<A::BaseField>>(air: &A, trace: &[Vec<A::BaseField>], options: &ProofOptions) -> Result<DeepProof<E>, VerifyError> {
+    options.validate()?;
+
+    if trace.len() != air.trace_width() || trace.iter().any(|column| column.len() != air.trace_length()) {
+        return Err(VerifyError::BadParameters("the trace doesn't have the shape of the Air"));
+    }
+
+    match options.hash_fn {
+        HashFunction::Blake2s => prove_deep_with::<A, E, Blake2s256>(air, trace, options),
+        HashFunction::Blake2b => prove_deep_with::<A, E, Blake2b256>(air, trace, options),
+        HashFunction::Sha256 => prove_deep_with::<A, E, Sha256>(air, trace, options),
+        HashFunction::Keccak256 => prove_deep_with::<A, E, Keccak256>(air, trace, options),
+        HashFunction::Blake3 => prove_deep_with::<A, E, Blake3>(air, trace, options),
+        HashFunction::RescueLike => prove_deep_with::<A, E, RescueLike<A::BaseField>>(air, trace, options),
+        HashFunction::PoseidonLike => prove_deep_with::<A, E, PoseidonLike<A::BaseField>>(air, trace, options),
+    }
+}
+
+fn prove_deep_with<A: Air, E: Field + From<A::BaseField>, H: Hasher>(air: &A, trace: &[Vec<A::BaseField>], options: &ProofOptions) -> Result<DeepProof<E>, VerifyError> {
+    let context = AirContext::new(air, options)?;
+    let mut transcript = deep_transcript::<A, H>(air, &context, options)?;
+
+    let num_steps = context.trace_length;
+    let extension_factor = context.extension_factor;
+    let precision = context.precision;
+    let width = context.trace_width;
+    let g2 = context.root_of_unity;
+
+    let xs = powers(g2, precision);
+
+    // the trace polynomials, and their evaluations over the extended domain
+    let p_polys: Vec<Vec<A::BaseField>> = trace.iter().map(|column| fft_inv(column, &xs[extension_factor])).collect();
+    let p_evaluations: Vec<Vec<A::BaseField>> = p_polys.iter().map(|poly| {
+        let mut evaluations = poly.clone();
+        evaluations.resize(precision, A::BaseField::ZERO);
+        fft_in_place(&mut evaluations, &g2);
+        evaluations
+    }).collect();
+
+    let trace_leaves: Vec<Value> = (0..precision).map(|i| p_evaluations.iter().flat_map(|p| p[i].to_bytes_be()).collect()).collect();
+    let trace_tree = MerkleTree::new::<H>(&trace_leaves, options.transcript, options.fri_folding_factor);
+
+    transcript.absorb_commitment(b"trace", &trace_tree.root());
+    let coefficients: Vec<E> = (0..num_composition_coefficients(&context)).map(|_| transcript.challenge_element(b"composition-coefficient")).collect();
+    let (transition_coefficients, rest) = coefficients.split_at(context.num_constraints);
+    let (boundary_coefficients, periodic_coefficients) = rest.split_at(width);
+
+    // H(x) is computed in coefficient form: the quotients are exact for a valid trace, so
+    // H(x) is right everywhere, including the points where the zerofiers vanish
+    let periodic_extensions: Vec<Vec<A::BaseField>> = context.periodic_columns.iter().map(|column| column.extend(g2, extension_factor)).collect();
+
+    let c_evaluations: Vec<Vec<A::BaseField>> = (0..precision).map(|i| {
+        let periodic_values: Vec<A::BaseField> = periodic_extensions.iter().map(|e| e[i % e.len()]).collect();
+        let current: Vec<A::BaseField> = p_evaluations.iter().map(|p| p[i]).collect();
+        let next: Vec<A::BaseField> = p_evaluations.iter().map(|p| p[(i + extension_factor) % precision]).collect();
+
+        let mut c_of_p = vec![A::BaseField::ZERO; context.num_constraints];
+        air.evaluate_transition(&current, &next, &periodic_values, &mut c_of_p);
+        c_of_p
+    }).collect();
+
+    let mut h_poly: Vec<E> = vec![E::ZERO; precision];
+    let mut add_quotient = |coefficient: E, quotient: Vec<A::BaseField>| {
+        for (h, q) in h_poly.iter_mut().zip(quotient) {
+            *h += coefficient * E::from(q);
+        }
+    };
+
+    // C(x) / Z(x) = C(x) * (x - last_step_position) / (x^steps - 1)
+    for (j, a) in transition_coefficients.iter().enumerate() {
+        let c_poly = fft_inv(&c_evaluations.iter().map(|c| c[j]).collect::<Vec<A::BaseField>>(), &g2);
+        let numerator = mul_polys(&c_poly, &[-context.last_step_position, A::BaseField::ONE]);
+        add_quotient(*a, div_by_sparse(&numerator, num_steps, A::BaseField::ONE));
+    }
+
+    for (r, b) in boundary_coefficients.iter().enumerate() {
+        let mut numerator = p_polys[r].clone();
+        for (n, i) in numerator.iter_mut().zip(&context.boundary_interpolants[r]) {
+            *n -= *i;
+        }
+        add_quotient(*b, div_by_monic(&numerator, &context.boundary_zerofiers[r]));
+    }
+
+    for (c, boundary) in periodic_coefficients.iter().zip(&context.periodic_boundaries) {
+        let mut numerator = p_polys[boundary.register].clone();
+        numerator[0] -= boundary.value;
+        add_quotient(*c, div_by_sparse(&numerator, boundary.num_steps, boundary.offset));
+    }
+
+    let mut h_evaluations = h_poly.clone();
+    fft_in_place(&mut h_evaluations, &E::from(g2));
+    let composition_tree = MerkleTree::new::<H>(&to_leaves(&h_evaluations), options.transcript, options.fri_folding_factor);
+
+    transcript.absorb_commitment(b"composition", &composition_tree.root());
+    let z: E = transcript.challenge_element(b"ood-point");
+    let g_z = z * E::from(xs[extension_factor]);
+
+    let mut ood_frame: Vec<E> = p_polys.iter().map(|p| eval_poly_at(p, z)).collect();
+    ood_frame.extend(p_polys.iter().map(|p| eval_poly_at(p, g_z)));
+    let ood_composition: E = eval_poly_at(&h_poly, z);
+
+    transcript.absorb_elements(b"ood-frame", &ood_frame);
+    transcript.absorb_elements(b"ood-composition", &[ood_composition]);
+
+    let deep_coefficients: Vec<E> = (0..2 * width + 1).map(|_| transcript.challenge_element(b"deep-coefficient")).collect();
+    let (current, next) = ood_frame.split_at(width);
+
+    let inv_x_minus_z = multi_inv(&xs.iter().map(|x| E::from(*x) - z).collect::<Vec<E>>());
+    let inv_x_minus_g_z = multi_inv(&xs.iter().map(|x| E::from(*x) - g_z).collect::<Vec<E>>());
+
+    let d_evaluations: Vec<E> = (0..precision).map(|i| {
+        let mut d = deep_coefficients[2 * width] * (h_evaluations[i] - ood_composition) * inv_x_minus_z[i];
+
+        for r in 0..width {
+            let p_of_x = E::from(p_evaluations[r][i]);
+            d += deep_coefficients[2 * r] * (p_of_x - current[r]) * inv_x_minus_z[i] + deep_coefficients[2 * r + 1] * (p_of_x - next[r]) * inv_x_minus_g_z[i];
+        }
+
+        d
+    }).collect();
+
+    let (fri_proof, positions) = prove_low_degree::<A::BaseField, E, H>(&mut transcript, d_evaluations, None, g2, context.degree_bound, None, options);
+
+    Ok(DeepProof {
+        trace_root: trace_tree.root(),
+        composition_root: composition_tree.root(),
+        ood_frame,
+        ood_composition,
+        fri_proof,
+        trace_branches: trace_tree.multi_branch(&positions),
+        composition_branches: composition_tree.multi_branch(&positions),
+    })
+}
+
+// the counterpart of verify_low_degree_proof: every layer evaluates the degree < 4
+// polynomials through the rows of the previous one at a random point. Without a tree for
+// `values` the first layer has no poly branches, the positions it queries are returned
+// for the caller to open its own commitments at.
+fn prove_low_degree<F: Field, E: Field + From<F>, H: Hasher>(transcript: &mut Transcript<H>, values: Vec<E>, tree: Option<&MerkleTree>, root_of_unity: F, max_deg_plus_1: usize, exclude_multiples_of: Option<u32>, options: &ProofOptions) -> (FRIProof<E>, Vec<u32>) {
+    let mut merkle_proofs: Vec<LDPMerkleProof> = Vec::new();
+    let mut values = values;
+    let mut root_of_unity = root_of_unity;
+    let mut max_deg_plus_1 = max_deg_plus_1;
+    let mut poly_tree: Option<MerkleTree> = None;
+    let mut first_positions: Vec<u32> = Vec::new();
+    let folding_factor = options.fri_folding_factor;
+
+    while max_deg_plus_1 > 16 {
+        let xs = powers(root_of_unity, values.len());
+        let column_len = values.len() / folding_factor;
+
+        let special_x: E = transcript.challenge_element(FRI_POINT);
+
+        let mut xcoords: Vec<E> = Vec::new();
+        let mut rows: Vec<E> = Vec::new();
+
+        for i in 0..column_len {
+            for j in 0..folding_factor {
+                xcoords.push(E::from(xs[i + column_len * j]));
+                rows.push(values[i + column_len * j]);
+            }
+        }
+
+        let column: Vec<E> = multi_interp(&xcoords, &rows, folding_factor).chunks(folding_factor).map(|p| eval_poly_at(p, special_x)).collect();
+        let column_tree = MerkleTree::new::<H>(&to_leaves(&column), options.transcript, options.fri_folding_factor);
+
+        transcript.absorb_commitment(b"fri-column", &column_tree.root());
+        let ys = transcript.challenge_indices(b"fri-queries", options.num_fri_queries, column_len as u32, exclude_multiples_of);
+
+        let mut poly_positions: Vec<u32> = Vec::new();
+
+        for y in &ys {
+            for j in 0..folding_factor {
+                poly_positions.push(y + (column_len * j) as u32);
+            }
+        }
+
+        let poly_branches = match poly_tree.as_ref().or(tree) {
+            Some(t) => t.multi_branch(&poly_positions),
+            None => MultiProof::default(),
+        };
+
+        if merkle_proofs.is_empty() {
+            first_positions = poly_positions;
+        }
+
+        merkle_proofs.push(LDPMerkleProof {
+            root2: column_tree.root(),
+            column_branches: column_tree.multi_branch(&ys),
+            poly_branches,
+        });
+
+        values = column;
+        poly_tree = Some(column_tree);
+        root_of_unity = root_of_unity.pow(folding_factor as u64);
+        max_deg_plus_1 /= folding_factor;
+    }
+
+    let proof = FRIProof {
+        merkle_proofs,
+        points_proof: values,
+    };
+
+    (proof, first_positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deserializer, serializer, verify, verify_mimc_proof};
+    use crate::deep::verify_deep;
+    use crate::air::{Air, Assertion, FibonacciAir};
+    use crate::field::{FieldElement, Goldilocks, QuadExtension, CubicExtension};
+    use crate::options::TranscriptMode;
+    use crate::utils::mimc;
+
+    fn constants<F: Field>() -> Vec<F> {
+        (0..64u64).map(|i| F::from(i.pow(7) ^ 42)).collect()
+    }
+
+    #[test]
+    fn test_reproduces_proof_bin() {
+        // same parameters and challenges as the python prover
+        let proof: StarkProof = prove_mimc(FieldElement::from(3u64), 8192, &constants(), &ProofOptions::legacy()).unwrap();
+        let expected = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/proof.bin")).unwrap();
+
+        assert!(serializer::to_bytes(&proof) == expected, "proof differs from proof.bin");
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        let options = ProofOptions::new(8, 40, 20, 4, HashFunction::RescueLike).unwrap();
+        let inp = Goldilocks::from(5u64);
+        let constants: Vec<Goldilocks> = constants()[..16].to_vec();
+        let output = mimc(inp, 512, &constants);
+
+        let proof: StarkProof<CubicExtension<Goldilocks>> = prove_mimc(inp, 512, &constants, &options).unwrap();
+        let proof: StarkProof<CubicExtension<Goldilocks>> = deserializer::from_bytes(&serializer::to_bytes(&proof)).unwrap();
+
+        let inputs = MimcPublicInputs::new(inp, 512, &constants, output);
+        assert_eq!(verify_mimc_proof(&inputs, &proof, &options), Ok(()));
+
+        // the labeled transcript binds the output, the legacy one doesn't
+        let wrong = MimcPublicInputs { output: output + Goldilocks::ONE, ..inputs.clone() };
+        assert!(verify_mimc_proof(&wrong, &proof, &options).is_err());
+        let legacy = ProofOptions { transcript: TranscriptMode::Legacy, ..options.clone() };
+        assert!(verify_mimc_proof(&inputs, &proof, &legacy).is_err());
+    }
+
+    // the register takes the values of a column of 8 constants one step late
+    struct CycleAir {
+        assertions: Vec<Assertion<Goldilocks>>,
+    }
+
+    impl CycleAir {
+        fn column() -> Vec<Goldilocks> {
+            (0..8u64).map(|i| Goldilocks::from(i * i + 1)).collect()
+        }
+
+        fn trace() -> Vec<Goldilocks> {
+            let column = CycleAir::column();
+            (0..256).map(|i| column[(i + 7) % 8]).collect()
+        }
+    }
+
+    impl Air for CycleAir {
+        type BaseField = Goldilocks;
+        type PublicInputs = Vec<Goldilocks>;
+
+        fn trace_length(&self) -> usize {
+            256
+        }
+
+        fn trace_width(&self) -> usize {
+            1
+        }
+
+        fn periodic_columns(&self) -> Vec<Vec<Goldilocks>> {
+            vec![CycleAir::column()]
+        }
+
+        fn transition_degrees(&self) -> Vec<usize> {
+            vec![1]
+        }
+
+        fn evaluate_transition<E: Field + From<Goldilocks>>(&self, _current: &[E], next: &[E], periodic_values: &[E], result: &mut [E]) {
+            result[0] = next[0] - periodic_values[0];
+        }
+
+        fn assertions(&self) -> Vec<Assertion<Goldilocks>> {
+            self.assertions.clone()
+        }
+
+        fn public_inputs(&self) -> Vec<Goldilocks> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_periodic_assertions() {
+        let options = ProofOptions::new(8, 40, 20, 4, HashFunction::Sha256).unwrap();
+        let column = CycleAir::column();
+        let air = CycleAir { assertions: vec![Assertion::periodic(0, 0, 8, column[7]), Assertion::periodic(0, 3, 8, column[2]), Assertion::single(0, 255, column[6])] };
+
+        let proof: StarkProof<QuadExtension<Goldilocks>> = prove(&air, &[CycleAir::trace()], &options).unwrap();
+        assert_eq!(verify(&air, &proof, &options), Ok(()));
+
+        // the values at steps 3, 11, ... don't match, whatever the transcript
+        let wrong = CycleAir { assertions: vec![Assertion::periodic(0, 0, 8, column[7]), Assertion::periodic(0, 3, 8, column[3]), Assertion::single(0, 255, column[6])] };
+        let legacy = ProofOptions { transcript: TranscriptMode::Legacy, ..options.clone() };
+        let proof: StarkProof<QuadExtension<Goldilocks>> = prove(&wrong, &[CycleAir::trace()], &legacy).unwrap();
+        assert!(verify(&wrong, &proof, &legacy).is_err());
+
+        let bad = CycleAir { assertions: vec![Assertion::periodic(0, 0, 6, column[7])] };
+        match prove::<_, QuadExtension<Goldilocks>>(&bad, &[CycleAir::trace()], &options) {
+            Err(VerifyError::BadParameters(_)) => {},
+            res => panic!("unexpected result {:?}", res.err()),
+        }
+    }
+
+    #[test]
+    fn test_two_register_trace() {
+        let options = ProofOptions::new(8, 40, 20, 4, HashFunction::Blake3).unwrap();
+        let trace = FibonacciAir::new(256, Goldilocks::ZERO).trace();
+        let air = FibonacciAir::new(256, trace[1][255]);
+
+        let proof: StarkProof<QuadExtension<Goldilocks>> = prove(&air, &trace, &options).unwrap();
+        let proof: StarkProof<QuadExtension<Goldilocks>> = deserializer::from_bytes(&serializer::to_bytes(&proof)).unwrap();
+        assert_eq!(verify(&air, &proof, &options), Ok(()));
+
+        let wrong = FibonacciAir::new(256, trace[1][255] + Goldilocks::ONE);
+        assert!(verify(&wrong, &proof, &options).is_err());
+
+        // the proof of a trace breaking the transitions doesn't verify
+        let mut bad_trace = trace.clone();
+        bad_trace[0][100] += Goldilocks::ONE;
+        let proof: StarkProof<QuadExtension<Goldilocks>> = prove(&air, &bad_trace, &options).unwrap();
+        assert!(verify(&air, &proof, &options).is_err());
+
+        match prove::<_, QuadExtension<Goldilocks>>(&air, &trace[..1], &options) {
+            Err(VerifyError::BadParameters(_)) => {},
+            res => panic!("unexpected result {:?}", res.err()),
+        }
+    }
+
+    // x -> x^3 + a * x + b + c with a, b and c cycling every 4, 16 and 1 steps
+    struct RoundsAir {
+        start: Goldilocks,
+        end: Goldilocks,
+    }
+
+    impl RoundsAir {
+        fn columns() -> Vec<Vec<Goldilocks>> {
+            [4u64, 16, 1].iter().map(|len| (0..*len).map(|i| Goldilocks::from(i * 31 + len)).collect()).collect()
+        }
+
+        fn trace(start: Goldilocks) -> Vec<Goldilocks> {
+            let columns = RoundsAir::columns();
+            let mut trace = vec![start];
+
+            for i in 0..255 {
+                let (x, values): (Goldilocks, Vec<Goldilocks>) = (trace[i], columns.iter().map(|c| c[i % c.len()]).collect());
+                trace.push(x * x.square() + values[0] * x + values[1] + values[2]);
+            }
+
+            trace
+        }
+    }
+
+    impl Air for RoundsAir {
+        type BaseField = Goldilocks;
+        type PublicInputs = Vec<Goldilocks>;
+
+        fn trace_length(&self) -> usize {
+            256
+        }
+
+        fn trace_width(&self) -> usize {
+            1
+        }
+
+        fn periodic_columns(&self) -> Vec<Vec<Goldilocks>> {
+            RoundsAir::columns()
+        }
+
+        fn transition_degrees(&self) -> Vec<usize> {
+            vec![3]
+        }
+
+        fn evaluate_transition<E: Field + From<Goldilocks>>(&self, current: &[E], next: &[E], periodic_values: &[E], result: &mut [E]) {
+            let x = current[0];
+            result[0] = next[0] - (x * x.square() + periodic_values[0] * x + periodic_values[1] + periodic_values[2]);
+        }
+
+        fn assertions(&self) -> Vec<Assertion<Goldilocks>> {
+            vec![Assertion::single(0, 0, self.start), Assertion::single(0, 255, self.end)]
+        }
+
+        fn public_inputs(&self) -> Vec<Goldilocks> {
+            vec![self.start, self.end]
+        }
+    }
+
+    #[test]
+    fn test_several_periodic_columns() {
+        let options = ProofOptions::new(8, 40, 20, 4, HashFunction::Blake2s).unwrap();
+        let trace = RoundsAir::trace(Goldilocks::from(2u64));
+        let air = RoundsAir { start: trace[0], end: trace[255] };
+
+        let proof: StarkProof<CubicExtension<Goldilocks>> = prove(&air, std::slice::from_ref(&trace), &options).unwrap();
+        assert_eq!(verify(&air, &proof, &options), Ok(()));
+
+        let wrong = RoundsAir { start: trace[0], end: trace[254] };
+        assert!(verify(&wrong, &proof, &options).is_err());
+    }
+
+    #[test]
+    fn test_folding_factors() {
+        let inp = Goldilocks::from(5u64);
+        let constants: Vec<Goldilocks> = constants()[..16].to_vec();
+        let inputs = MimcPublicInputs::new(inp, 512, &constants, mimc(inp, 512, &constants));
+
+        for folding_factor in [2usize, 4, 8, 16].iter() {
+            let options = ProofOptions::new(8, 40, 20, *folding_factor, HashFunction::Blake2s).unwrap();
+            let proof: StarkProof<CubicExtension<Goldilocks>> = prove_mimc(inp, 512, &constants, &options).unwrap();
+            assert_eq!(verify_mimc_proof(&inputs, &proof, &options), Ok(()));
+
+            // the folding factor is absorbed into the transcript, and fixes the number of layers
+            let other = ProofOptions { fri_folding_factor: if *folding_factor == 2 { 4 } else { 2 }, ..options.clone() };
+            assert!(verify_mimc_proof(&inputs, &proof, &other).is_err());
+
+            let air = MimcAir::new(inputs.clone());
+            let proof: DeepProof<CubicExtension<Goldilocks>> = prove_deep(&air, &[air.trace()], &options).unwrap();
+            assert_eq!(verify_deep(&air, &proof, &options), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_bad_parameters() {
+        let constants: Vec<FieldElement> = constants();
+
+        match prove_mimc::<FieldElement, FieldElement>(FieldElement::ONE, 100, &constants, &ProofOptions::default()) {
+            Err(VerifyError::BadParameters(_)) => {},
+            res => panic!("unexpected result {:?}", res.err()),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::deserializer::{MERKLE, POINTS};
+use crate::field::Field;
+use crate::merkle_tree::MultiProof;
+use crate::proof::*;
+
+fn write_u32(output: &mut Vec<u8>, value: usize) {
+    output.extend_from_slice(&(value as u32).to_le_bytes());
+}
+
+fn write_multiproof(output: &mut Vec<u8>, proof: &MultiProof) {
+    // the root isn't serialized, the verifier gets it from the commitment
+    write_u32(output, proof.branches.len());
+
+    for branch in &proof.branches {
+        write_u32(output, branch.value.len());
+        output.extend_from_slice(&branch.value);
+        output.extend_from_slice(&branch.sibling_value);
+
+        write_u32(output, 32 * branch.witnesses.len());
+        for witness in &branch.witnesses {
+            output.extend_from_slice(witness);
+        }
+    }
+}
+
+fn write_ldp_merkle_proof(output: &mut Vec<u8>, proof: &LDPMerkleProof) {
+    write_u32(output, MERKLE as usize);
+    output.extend_from_slice(&proof.root2);
+    write_multiproof(output, &proof.column_branches);
+    write_multiproof(output, &proof.poly_branches);
+}
+
+/// Serializes the branches of a multiproof: their number, then the value, sibling value and
+/// witnesses of each branch
+pub fn multiproof_to_bytes(proof: &MultiProof) -> Vec<u8> {
+    let mut output: Vec<u8> = Vec::new();
+    write_multiproof(&mut output, proof);
+    output
+}
+
+/// Serializes a FRI layer as it appears in a proof, starting with its element type
+pub fn ldp_merkle_proof_to_bytes(proof: &LDPMerkleProof) -> Vec<u8> {
+    let mut output: Vec<u8> = Vec::new();
+    write_ldp_merkle_proof(&mut output, proof);
+    output
+}
+
+/// Serializes a proof in the format `deserializer::from_bytes` parses
+pub fn to_bytes<F: Field>(proof: &StarkProof<F>) -> Vec<u8> {
+    let mut output: Vec<u8> = Vec::new();
+
+    output.extend_from_slice(&proof.merkle_root);
+    output.extend_from_slice(&proof.l_merkle_root);
+
+    for m in &proof.fri_proof.merkle_proofs {
+        write_ldp_merkle_proof(&mut output, m);
+    }
+
+    write_u32(&mut output, POINTS as usize);
+    write_u32(&mut output, F::ELEMENT_BYTES * proof.fri_proof.points_proof.len());
+    for point in &proof.fri_proof.points_proof {
+        output.extend_from_slice(&point.to_bytes_be());
+    }
+
+    write_multiproof(&mut output, &proof.merkle_branches);
+    write_multiproof(&mut output, &proof.linear_comb_branches);
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deserializer::from_bytes;
+    use crate::field::FieldElement;
+
+    fn proof_bytes() -> Vec<u8> {
+        std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/proof.bin")).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bytes = proof_bytes();
+        let proof = from_bytes::<FieldElement>(&bytes).unwrap();
+
+        assert!(to_bytes(&proof) == bytes, "serialized proof differs from proof.bin");
+    }
+
+    #[test]
+    fn test_parts_round_trip() {
+        let bytes = proof_bytes();
+        let proof = from_bytes::<FieldElement>(&bytes).unwrap();
+
+        // the first FRI layer follows the two roots, the linear combination branches end the proof
+        let layer = ldp_merkle_proof_to_bytes(&proof.fri_proof.merkle_proofs[0]);
+        assert_eq!(&bytes[64..64 + layer.len()], &layer[..]);
+
+        let branches = multiproof_to_bytes(&proof.linear_comb_branches);
+        assert_eq!(&bytes[bytes.len() - branches.len()..], &branches[..]);
+
+        let empty = MultiProof::default();
+        assert_eq!(multiproof_to_bytes(&empty), vec![0u8; 4]);
+    }
+}
@@ -0,0 +1,187 @@
+use std::marker::PhantomData;
+
+use crate::field::Field;
+use crate::hash::Hasher;
+use crate::merkle_tree::MerkleDigest;
+use crate::options::TranscriptMode;
+use crate::utils::get_pseudorandom_indices;
+
+/// Label of the point each FRI layer is evaluated at
+pub const FRI_POINT: &[u8] = b"fri-point";
+
+/// The legacy transcript numbers the challenges after a commitment with a single byte
+pub const LEGACY_MAX_CHALLENGES: usize = 255;
+
+/// Fiat-Shamir transcript the verifier draws its challenges from. Public inputs and
+/// commitments are absorbed with a label, and every challenge depends on everything
+/// absorbed before it and on its own label.
+///
+/// In `TranscriptMode::Legacy` the transcript reproduces the python prover instead:
+/// public inputs are ignored, the constraint coefficients are H(root || i) for the last
+/// commitment, FRI_POINT is that root read as an element and indices are chained hashes
+/// of it.
+pub struct Transcript<H: Hasher> {
+    mode: TranscriptMode,
+    state: MerkleDigest,
+    // challenges drawn since the last absorb
+    counter: u64,
+    _hasher: PhantomData<H>,
+}
+
+// the length prefix keeps the encoding of consecutive fields unambiguous
+fn frame(bytes: &[u8]) -> Vec<u8> {
+    [&(bytes.len() as u64).to_be_bytes()[..], bytes].concat()
+}
+
+impl<H: Hasher> Transcript<H> {
+    pub fn new(mode: TranscriptMode, protocol: &[u8]) -> Self {
+        let mut transcript = Transcript {
+            mode,
+            state: [0u8; 32],
+            counter: 0,
+            _hasher: PhantomData,
+        };
+
+        transcript.absorb(b"protocol", protocol);
+        transcript
+    }
+
+    /// Absorbs public data, e.g. the inputs of the computation
+    pub fn absorb(&mut self, label: &[u8], data: &[u8]) {
+        if self.mode == TranscriptMode::Legacy {
+            return;
+        }
+
+        self.state = H::hash_leaf(&[&self.state[..], &frame(label), &frame(data)].concat());
+        self.counter = 0;
+    }
+
+    pub fn absorb_elements<F: Field>(&mut self, label: &[u8], values: &[F]) {
+        let bytes: Vec<u8> = values.iter().flat_map(|x| x.to_bytes_be()).collect();
+        self.absorb(label, &bytes);
+    }
+
+    /// Absorbs the root of a commitment
+    pub fn absorb_commitment(&mut self, label: &[u8], root: &MerkleDigest) {
+        match self.mode {
+            TranscriptMode::Legacy => {
+                self.state = *root;
+                self.counter = 0;
+            },
+            TranscriptMode::Labeled => self.absorb(label, root),
+        }
+    }
+
+    fn squeeze(&mut self, label: &[u8]) -> MerkleDigest {
+        self.counter += 1;
+        H::hash_leaf(&[&self.state[..], &frame(label), &self.counter.to_be_bytes()].concat())
+    }
+
+    pub fn challenge_element<E: Field>(&mut self, label: &[u8]) -> E {
+        match self.mode {
+            TranscriptMode::Legacy if label == FRI_POINT => E::from_bytes_be_reduced(&self.state),
+            TranscriptMode::Legacy => {
+                self.counter += 1;
+                assert!(self.counter as usize <= LEGACY_MAX_CHALLENGES, "too many challenges for the legacy transcript");
+                E::from_bytes_be_reduced(&H::hash_leaf(&[&self.state[..], &[self.counter as u8]].concat()))
+            },
+            TranscriptMode::Labeled => E::from_bytes_be_reduced(&self.squeeze(label)),
+        }
+    }
+
+    /// `count` indices below `modulus`, skipping the multiples of `exclude_multiples_of`
+    pub fn challenge_indices(&mut self, label: &[u8], count: usize, modulus: u32, exclude_multiples_of: Option<u32>) -> Vec<u32> {
+        if self.mode == TranscriptMode::Legacy {
+            return get_pseudorandom_indices::<H>(&self.state, count, modulus, exclude_multiples_of);
+        }
+
+        let real_modulus = match exclude_multiples_of {
+            Some(exclude) => (modulus as u64) * (exclude as u64 - 1) / exclude as u64,
+            None => modulus as u64,
+        };
+
+        let mut output: Vec<u32> = Vec::new();
+
+        while output.len() < count {
+            // 64 bit samples keep the modulo bias negligible
+            for chunk in self.squeeze(label).chunks(8) {
+                let mut sample = [0u8; 8];
+                sample.copy_from_slice(chunk);
+                output.push((u64::from_be_bytes(sample) % real_modulus) as u32);
+            }
+        }
+
+        output.truncate(count);
+
+        if let Some(exclude) = exclude_multiples_of {
+            output = output.iter().map(|x| 1 + x + x / (exclude - 1)).collect();
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldElement;
+    use crate::hash::Blake2s256;
+
+    fn transcript() -> Transcript<Blake2s256> {
+        let mut transcript = Transcript::new(TranscriptMode::Labeled, b"test");
+        transcript.absorb(b"input", &[1, 2, 3]);
+        transcript.absorb_commitment(b"root", &[7u8; 32]);
+        transcript
+    }
+
+    #[test]
+    fn test_challenges_depend_on_everything_absorbed() {
+        let a: FieldElement = transcript().challenge_element(b"k");
+        assert_eq!(a, transcript().challenge_element(b"k"));
+
+        // the label, the data and where one ends and the next starts are all bound
+        let mut other = Transcript::<Blake2s256>::new(TranscriptMode::Labeled, b"test");
+        other.absorb(b"input", &[1, 2]);
+        other.absorb(b"", &[3]);
+        other.absorb_commitment(b"root", &[7u8; 32]);
+        assert_ne!(a, other.challenge_element(b"k"));
+
+        let mut other = Transcript::<Blake2s256>::new(TranscriptMode::Labeled, b"test");
+        other.absorb(b"inpu", &[b't', 1, 2, 3]);
+        other.absorb_commitment(b"root", &[7u8; 32]);
+        assert_ne!(a, other.challenge_element(b"k"));
+
+        assert_ne!(a, transcript().challenge_element(b"j"));
+
+        // consecutive challenges differ
+        let mut t = transcript();
+        let first: FieldElement = t.challenge_element(b"k");
+        assert_ne!(first, t.challenge_element(b"k"));
+    }
+
+    #[test]
+    fn test_challenge_indices() {
+        let indices = transcript().challenge_indices(b"queries", 100, 1024, Some(8));
+        assert_eq!(indices.len(), 100);
+        assert!(indices.iter().all(|i| *i < 1024 && i % 8 != 0));
+
+        let indices = transcript().challenge_indices(b"queries", 3, 10, None);
+        assert_eq!(indices.len(), 3);
+        assert!(indices.iter().all(|i| *i < 10));
+    }
+
+    #[test]
+    fn test_legacy_mode() {
+        let root = [9u8; 32];
+        let mut transcript = Transcript::<Blake2s256>::new(TranscriptMode::Legacy, b"test");
+        transcript.absorb(b"input", &[1, 2, 3]);
+        transcript.absorb_commitment(b"root", &root);
+
+        let k1: FieldElement = transcript.challenge_element(b"k");
+        let k2: FieldElement = transcript.challenge_element(b"k");
+        assert_eq!(k1, FieldElement::from_bytes_be_reduced(&Blake2s256::hash_leaf(&[&root[..], &[1]].concat())));
+        assert_eq!(k2, FieldElement::from_bytes_be_reduced(&Blake2s256::hash_leaf(&[&root[..], &[2]].concat())));
+        assert_eq!(transcript.challenge_element::<FieldElement>(FRI_POINT), FieldElement::from_bytes_be_reduced(&root));
+        assert_eq!(transcript.challenge_indices(b"queries", 10, 64, Some(8)), get_pseudorandom_indices::<Blake2s256>(&root, 10, 64, Some(8)));
+    }
+}
@@ -1,48 +1,37 @@
-use num_bigint::{BigInt, Sign, ToBigInt};
-use blake2::{Blake2s, Digest};
-use std::mem::transmute;
-use rustfft::num_traits::Pow;
-use rustfft::num_traits::identities::{Zero, One};
-use rustfft::num_traits::sign::Signed;
+use crate::field::Field;
+use crate::hash::Hasher;
 
-pub fn mimc(input: &BigInt, steps: usize, round_constants: &Vec<BigInt>, modulus: &BigInt) -> BigInt {
-    let mut output = input.clone();
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
-	for i in 0..(steps-1) {
-      output = negative_to_positive(&(output.pow(3u32) + round_constants[i % round_constants.len()].clone()), modulus);
-	}
+pub fn mimc<F: Field>(input: F, steps: usize, round_constants: &[F]) -> F {
+    let mut output = input;
+
+    for i in 0..(steps-1) {
+        output = output * output.square() + round_constants[i % round_constants.len()];
+    }
 
     output
 }
 
-/* 
-  TODO make the first argument not be passed by reference
-  Convert a negative number to positive.
-  Inspiration:
-  https://internals.rust-lang.org/t/mathematical-modulo-operator/5952
-  https://math.stackexchange.com/questions/519845/modulo-of-a-negative-number
-*/
-pub fn negative_to_positive(n: &BigInt, modulus: &BigInt) -> BigInt {
-    if !n.is_negative() {
-        return n.clone() % modulus;
-    }
+/// Maps `f` over `items`, across threads when the `parallel` feature is enabled. The
+/// output is in the order of `items` either way.
+pub fn par_map<T: Sync, R: Send, G: Fn(&T) -> R + Sync + Send>(items: &[T], f: G) -> Vec<R> {
+    #[cfg(feature = "parallel")]
+    let output = items.par_iter().map(f).collect();
 
-    let mut k: BigInt = BigInt::from(1u8);
-    let mul_fac: BigInt = BigInt::from(10u8);
-    let mut res = n.clone() * -BigInt::one();
+    #[cfg(not(feature = "parallel"))]
+    let output = items.iter().map(f).collect();
 
-    loop {
-        if (modulus * &k) > res {
-            let res = ((modulus * &k) - res) % modulus;
-            return res;
-        }
+    output
+}
 
-        k = k * &mul_fac;
-    }
+/// The first error in `results`, so that a parallel run fails exactly like a sequential one
+pub fn first_error<E>(results: Vec<Result<(), E>>) -> Result<(), E> {
+    results.into_iter().collect()
 }
 
-// https://stackoverflow.com/questions/600293/how-to-check-if-a-number-is-a-power-of-2
-pub fn is_power_of_2(n: u32) -> bool {
+pub fn is_power_of_2(n: usize) -> bool {
     if n == 0 {
         false
     } else {
@@ -50,225 +39,338 @@ pub fn is_power_of_2(n: u32) -> bool {
     }
 }
 
-pub fn get_pseudorandom_indices(seed: &[u8; 32], count: usize, modulus: u32, excludeMultiplesOf: Option<u32>) -> Vec<u32> {
-    let mut hasher = Blake2s::default();
-    let mut hashes: Vec<u8> = vec![0u8; 32];
-    let mut output: Vec<u32> = Vec::new();
+pub fn get_pseudorandom_indices<H: Hasher>(seed: &[u8; 32], count: usize, modulus: u32, exclude_multiples_of: Option<u32>) -> Vec<u32> {
+    let mut hashes: Vec<u8> = seed.to_vec();
 
-    let real_modulus: u32 = match excludeMultiplesOf {
+    let real_modulus: u32 = match exclude_multiples_of {
         Some(exclude) => {
-            modulus * ( exclude - 1 ) / exclude
+            ((modulus as u64) * ( exclude as u64 - 1 ) / exclude as u64) as u32
         },
         None => {
             modulus
         }
     };
 
-    hashes[0..32].clone_from_slice(seed/*&hasher.result().clone()*/);
-
     while hashes.len() < 4 * count {
-        hasher = Blake2s::default();
-        hasher.input(&hashes[hashes.len()-32..]);
-        let result = hasher.result();
+        let result = H::hash_leaf(&hashes[hashes.len()-32..]);
         hashes.extend_from_slice(&result);
     }
 
-    for j in (0..(count*4)).step_by(4) {
+    let mut output: Vec<u32> = hashes[..4 * count].chunks(4).map(|chunk| {
         let mut index = [0u8; 4];
-        index.clone_from_slice(&hashes[j..j+4]);
-        index = [index[3], index[2], index[1], index[0]];
-
-        unsafe {
-            let x = transmute::<[u8; 4], u32>(index);
-            //println!("x is {:x?}", &x);
-            //println!("x mod modulus is {:x?}", x % &real_modulus);
-            output.push(transmute::<[u8; 4], u32>(index) % real_modulus);
-        }
-    }
+        index.copy_from_slice(chunk);
+        u32::from_be_bytes(index) % real_modulus
+    }).collect();
 
-    if let Some(exclude) = excludeMultiplesOf {
+    if let Some(exclude) = exclude_multiples_of {
         output = output.iter().map(|x| 1+x+(x/(exclude-1))).collect();
     }
 
-    output 
+    output
 }
 
 pub fn as_u32_le(array: &[u8; 4]) -> u32 {
-    ((array[0] as u32) <<  0) +
-    ((array[1] as u32) <<  8) +
-    ((array[2] as u32) << 16) +
-    ((array[3] as u32) << 24)
+    u32::from_le_bytes(*array)
 }
 
-pub fn eval_quartic(eq: &[BigInt], y: &BigInt, m: &BigInt) -> BigInt {
+pub fn eval_quartic<F: Field>(eq: &[F], x: F) -> F {
     assert!(eq.len() == 4, "only quartic equations supported");
-    let modulus = &BigInt::from(m.clone());
-    let x = BigInt::from(y.clone());
-    let xsq = ( &x * &x ) % modulus;
-    let xcb =  &x * &xsq;
+    let xsq = x.square();
+    let xcb = x * xsq;
 
-    let res: BigInt = &(eq[0]) + &(eq[1]) * &x + &(eq[2]) * &xsq + &(eq[3]) * &xcb;
+    eq[0] + eq[1] * x + eq[2] * xsq + eq[3] * xcb
+}
 
-    match res.is_negative() {
-        true => {
-            negative_to_positive(&res, m) % m 
-        },
-        false => {
-            res % m 
-        }
+pub fn eval_poly_at<F: Field, E: Field + From<F>>(poly: &[F], x: E) -> E {
+    let mut y = E::ZERO;
+    let mut power_of_x = E::ONE;
+
+    for p_coef in poly.iter() {
+        y += power_of_x * E::from(*p_coef);
+        power_of_x *= x;
     }
-}
 
-pub fn divmod(x: &BigInt, y: &BigInt, m: &BigInt) -> BigInt {
-    return (x * &inv(y.clone(), m.clone())) % m;
+    y
 }
 
-pub fn eval_poly_at(poly: &Vec<BigInt>, x: &BigInt, m: &BigInt) -> BigInt {
-    let mut y = BigInt::zero();
-    let mut power_of_x = BigInt::one();
+// inverts all values with a single field inversion, zeros are left as zeros
+pub fn multi_inv<F: Field>(values: &[F]) -> Vec<F> {
+    let mut partials: Vec<F> = vec![F::ONE];
 
-    for p_coef in poly.iter() {
-        y += &power_of_x * p_coef;
-        power_of_x = (power_of_x * x) % m;
+    for value in values {
+        if value.is_zero() {
+            partials.push(partials[partials.len()-1]);
+        } else {
+            partials.push(partials[partials.len()-1] * *value);
+        }
     }
 
-    return y % m;
+    let mut inv = partials[partials.len()-1].inv();
+    let mut outputs: Vec<F> = vec![F::ZERO; values.len()];
+
+    for i in (1..values.len()+1).rev() {
+        if !values[i-1].is_zero() {
+            outputs[i-1] = partials[i-1] * inv;
+            inv *= values[i-1];
+        }
+    }
+
+    outputs
 }
 
-// modular inverse
-fn inv(x: BigInt, m: BigInt) -> BigInt {
-	let a = BigInt::from(x);
+pub fn multi_interp_4<F: Field>(xsets: &[F], ysets: &[F]) -> Vec<F> {
+    assert!(xsets.len() == ysets.len(), "number of xs should be equal to number of ys");
+
+    let mut data: Vec<(&[F], [[F; 4]; 4])> = Vec::new();
+    let mut inv_targets: Vec<F> = Vec::new();
+    let mut output: Vec<F> = Vec::new();
+
+    for i in (0..xsets.len()).step_by(4) {
+        let x = &xsets[i..i+4];
+        let x01 = x[0] * x[1];
+        let x02 = x[0] * x[2];
+        let x03 = x[0] * x[3];
+        let x12 = x[1] * x[2];
+        let x13 = x[1] * x[3];
+        let x23 = x[2] * x[3];
+
+        let eq0 = [-(x12 * x[3]), x12 + x13 + x23, -x[1] - x[2] - x[3], F::ONE];
+        let eq1 = [-(x02 * x[3]), x02 + x03 + x23, -x[0] - x[2] - x[3], F::ONE];
+        let eq2 = [-(x01 * x[3]), x01 + x03 + x13, -x[0] - x[1] - x[3], F::ONE];
+        let eq3 = [-(x01 * x[2]), x01 + x02 + x12, -x[0] - x[1] - x[2], F::ONE];
+
+        inv_targets.push(eval_quartic(&eq0, x[0]));
+        inv_targets.push(eval_quartic(&eq1, x[1]));
+        inv_targets.push(eval_quartic(&eq2, x[2]));
+        inv_targets.push(eval_quartic(&eq3, x[3]));
+
+        data.push((&ysets[i..i+4], [eq0, eq1, eq2, eq3]));
+    }
+
+    let inv_vals = multi_inv(&inv_targets);
 
-    let modulus = BigInt::from(m); 
+    for (i, (ys, eqs)) in data.iter().enumerate() {
+        let inv_y0 = ys[0] * inv_vals[i*4];
+        let inv_y1 = ys[1] * inv_vals[i*4 + 1];
+        let inv_y2 = ys[2] * inv_vals[i*4 + 2];
+        let inv_y3 = ys[3] * inv_vals[i*4 + 3];
 
-    if a == BigInt::zero() {
-        return BigInt::zero();
+        output.extend((0..4).map(|j| eqs[0][j] * inv_y0 + eqs[1][j] * inv_y1 + eqs[2][j] * inv_y2 + eqs[3][j] * inv_y3));
     }
 
-    let mut lm = BigInt::one();
-    let mut hm = BigInt::zero();
-    let mut low = negative_to_positive(&a, &modulus);
-    let mut high = modulus.clone();
+    output
+}
+
+// interpolates every `width` consecutive points, sharing one inversion like multi_interp_4
+// which it defers to for batches of 4
+pub fn multi_interp<F: Field>(xsets: &[F], ysets: &[F], width: usize) -> Vec<F> {
+    assert!(xsets.len() == ysets.len(), "number of xs should be equal to number of ys");
+
+    if width == 4 {
+        return multi_interp_4(xsets, ysets);
+    }
+
+    let mut nums: Vec<Vec<F>> = Vec::new();
+    let mut denoms: Vec<F> = Vec::new();
+
+    for xs in xsets.chunks(width) {
+        let root = zpoly(xs);
+
+        for x in xs {
+            let num = div_by_linear(&root, *x);
+            denoms.push(eval_poly_at(&num, *x));
+            nums.push(num);
+        }
+    }
 
-    while low > BigInt::one() {
-        let r = &high / &low;
+    let inv_denoms = multi_inv(&denoms);
+    let mut output: Vec<F> = vec![F::ZERO; xsets.len()];
 
-        let nm = &hm - &lm * &r;
-        let new = &high - &low * &r;
+    for (i, num) in nums.iter().enumerate() {
+        let y = ysets[i] * inv_denoms[i];
+        let start = i - i % width;
 
-        // TODO how can we avoid cloning here (maybe using mem::swap?)
-        hm = lm.clone();
-		lm = nm;
-        high = low.clone();
-        low = new;
+        for (o, c) in output[start..start + width].iter_mut().zip(num) {
+            *o += *c * y;
+        }
     }
 
-    negative_to_positive(&lm, &modulus) % &modulus
+    output
 }
 
-pub fn multi_inv(values: &[BigInt], modulus: &BigInt) -> Vec<BigInt> {
-    let mut partials: Vec<BigInt> = vec![BigInt::from(1u8)];
+pub fn mul_polys<F: Field>(p0: &[F], p1: &[F]) -> Vec<F> {
+    let mut output: Vec<F> = vec![F::ZERO; p0.len() + p1.len() - 1];
 
-    for i in 0..(values.len()) {
-        if values[i] == BigInt::from(0u8) {
-            partials.push(&(partials[partials.len()-1]) % modulus);
-        } else {
-            partials.push(&(partials[partials.len()-1]) * &(values[i]) % modulus);
+    for (i, a) in p0.iter().enumerate() {
+        for (j, b) in p1.iter().enumerate() {
+            output[i+j] += *a * *b;
         }
     }
 
-    let mut inv = inv(partials[partials.len()-1].clone(), modulus.clone());
-    let mut outputs: Vec<BigInt> = vec![BigInt::from(0u8); values.len()];
+    output
+}
 
-    for i in (1..values.len()+1).rev() {
-        if values[i-1] == BigInt::from(0u8) {
-            outputs[i-1] = BigInt::from(0u8);
-            inv = inv % modulus;
-        } else {
-            outputs[i-1] = (&(partials[i-1]) * &inv) % modulus;
-            inv = (inv * &(values[i-1])) % modulus;
+// polynomial with a root at each x: (x - xs[0]) * (x - xs[1]) * ...
+pub fn zpoly<F: Field>(xs: &[F]) -> Vec<F> {
+    let mut root: Vec<F> = vec![F::ONE];
+
+    for x in xs {
+        root.insert(0, F::ZERO);
+
+        for j in 0..(root.len()-1) {
+            let next = root[j+1];
+            root[j] -= next * *x;
         }
     }
 
-    outputs
+    root
 }
 
-pub fn multi_interp_4(xsets: &Vec<BigInt>, ysets: &Vec<BigInt>, modulus: &BigInt) -> Vec<BigInt> {
-	assert!(xsets.len() == ysets.len(), "number of xs should be equal to number of ys");
+// divide a polynomial by (x - a), the remainder is dropped
+fn div_by_linear<F: Field>(poly: &[F], a: F) -> Vec<F> {
+    let mut output: Vec<F> = vec![F::ZERO; poly.len() - 1];
+    let mut carry = F::ZERO;
 
-    let mut data: Vec<(&[BigInt], [[BigInt; 4]; 4])> = Vec::new();
-    let mut inv_targets: Vec<BigInt> = Vec::new();
-    let mut output: Vec<BigInt> = Vec::new();
+    for i in (1..poly.len()).rev() {
+        carry = poly[i] + carry * a;
+        output[i-1] = carry;
+    }
 
-	for i in (0..xsets.len()).step_by(4) {
-        let x01 = &(xsets[i]) * &(xsets[i+1]);
-        let x02 = &(xsets[i]) * &(xsets[i+2]);
-        let x03 = &(xsets[i]) * &(xsets[i+3]);
-        let x12 = &(xsets[i+1]) * &(xsets[i+2]);
-        let x13 = &(xsets[i+1]) * &(xsets[i+3]);
-        let x23 = &(xsets[i+2]) * &(xsets[i+3]);
+    output
+}
 
-        let eq0: [BigInt; 4] = [negative_to_positive(&-(&x12 * &(xsets[i+3])), modulus) % modulus, &x12 + &x13 + &x23, -&xsets[i+1] - &(xsets[i+2]) - &(xsets[i+3]), BigInt::from(1u8)];
-        let eq1 = [negative_to_positive(&(-&x02 * &(xsets[i+3])), modulus) % modulus, &x02 + &x03 + &x23, -&(xsets[i]) - &(xsets[i+2]) - &(xsets[i+3]), BigInt::from(1u8)];
-        let eq2 = [negative_to_positive(&(-&x01 * &(xsets[i+3])), modulus) % modulus, &x01 + &x03 + &x13, -&(xsets[i]) - &(xsets[i+1]) - &(xsets[i+3]), BigInt::from(1u8)];
-        let eq3 = [negative_to_positive(&(-&x01 * &(xsets[i+2])), modulus) % modulus, &x01 + &x02 + &x12, -&(xsets[i]) - &(xsets[i+1]) - &(xsets[i+2]), BigInt::from(1u8)];
+// divide a polynomial by x^k - c, the remainder is dropped
+pub fn div_by_sparse<F: Field>(poly: &[F], k: usize, c: F) -> Vec<F> {
+    let mut output: Vec<F> = vec![F::ZERO; poly.len().saturating_sub(k)];
 
-        let e0 = eval_quartic(&eq0, &xsets[i], modulus);
-        let e1 = eval_quartic(&eq1, &xsets[i+1], modulus);
-        let e2 = eval_quartic(&eq2, &xsets[i+2], modulus);
-        let e3 = eval_quartic(&eq3, &xsets[i+3], modulus);
+    for i in (0..output.len()).rev() {
+        let carry = if i + k < output.len() { output[i + k] } else { F::ZERO };
+        output[i] = poly[i + k] + c * carry;
+    }
 
-        data.push((&ysets[i..i+4], [eq0, eq1, eq2, eq3]));
-        inv_targets.push(e0);
-        inv_targets.push(e1);
-        inv_targets.push(e2);
-        inv_targets.push(e3);
-	}
+    output
+}
 
-    let inv_vals = multi_inv(&inv_targets, &modulus);
+// divide a polynomial by one with leading coefficient 1, the remainder is dropped
+pub fn div_by_monic<F: Field>(poly: &[F], divisor: &[F]) -> Vec<F> {
+    let degree = divisor.len() - 1;
+    let mut remainder = poly.to_vec();
+    let mut output: Vec<F> = vec![F::ZERO; poly.len().saturating_sub(degree)];
 
-    for (i, (ys, eqs)) in data.iter().enumerate() {
-        let inv_y0 = &(ys[0]) * &(inv_vals[i*4]) % modulus;
-        let inv_y1 = &(ys[1]) * &(inv_vals[i*4 + 1]) % modulus;
-        let inv_y2 = &(ys[2]) * &(inv_vals[i*4 + 2]) % modulus;
-        let inv_y3 = &(ys[3]) * &(inv_vals[i*4 + 3]) % modulus;
-
-        for j in 0..4 {
-            let mut output_val = &(eqs[0][j]) * &inv_y0 + &(eqs[1][j]) * &inv_y1 + &(eqs[2][j]) * &inv_y2 + &(eqs[3][j]) * &inv_y3;
-            output_val = negative_to_positive(&output_val, modulus) % modulus;
-            output.push(output_val);
+    for i in (0..output.len()).rev() {
+        let q = remainder[i + degree];
+        output[i] = q;
+
+        for (j, d) in divisor.iter().enumerate() {
+            remainder[i + j] -= q * *d;
         }
     }
 
     output
 }
 
-pub fn mul_polys(p0: &Vec<BigInt>, p1: &Vec<BigInt>, modulus: &BigInt) -> Vec<BigInt> {
-    assert!(p0.len() == p1.len(), "polynomials must be the same degree");
+// polynomial of degree < xs.len() going through each (xs[i], ys[i])
+pub fn lagrange_interp<F: Field>(xs: &[F], ys: &[F]) -> Vec<F> {
+    assert!(xs.len() == ys.len(), "number of xs should be equal to number of ys");
+
+    let root = zpoly(xs);
+    let nums: Vec<Vec<F>> = xs.iter().map(|x| div_by_linear(&root, *x)).collect();
+    let denoms: Vec<F> = nums.iter().zip(xs.iter()).map(|(num, x)| eval_poly_at(num, *x)).collect();
+    let invdenoms = multi_inv(&denoms);
+
+    let mut output: Vec<F> = vec![F::ZERO; ys.len()];
 
-    //TODO this seems like pointless double memory allocation (but necessary for rust to compile)
-    let mut output: Vec<BigInt> = vec![BigInt::zero(); p0.len() + p1.len() - 1];
+    for i in 0..xs.len() {
+        let yslice = ys[i] * invdenoms[i];
 
-    for (i, _) in p0.iter().enumerate() {
-        for (j, _) in p1.iter().enumerate() {
-            output[i+j] += &p0[i] * &p1[j];
+        for j in 0..ys.len() {
+            output[j] += nums[i][j] * yslice;
         }
     }
 
-    output.iter().map(|x| x  % modulus).collect()
+    output
 }
 
-pub fn lagrange_interp_2(xs: &[BigInt; 2], ys: &[BigInt; 2], modulus: &BigInt) -> [BigInt; 2] {
-    let eq0 = [negative_to_positive(&-&xs[1], modulus), BigInt::one()];
-    let eq1 = [negative_to_positive(&-&xs[0], modulus), BigInt::one()];
-    let e0 = eval_poly_at(&eq0.to_vec(), &xs[0], modulus);
-    let e1 = eval_poly_at(&eq1.to_vec(), &xs[1], modulus);
-    let inv_val = inv(&e0 * &e1, modulus.clone());
-    let inv_y0 = &ys[0] * &inv_val * &e1;
-    let inv_y1 = &ys[1] * &inv_val * &e0;
-    let int1 = negative_to_positive(&(&eq0[0] * &inv_y0 + &eq1[0] * &inv_y1), modulus);
-    let int2 = negative_to_positive(&(&eq0[1] * &inv_y0 + &eq1[1] * &inv_y1), modulus);
-
-    [int1,
-     int2]
+pub fn lagrange_interp_2<F: Field>(xs: &[F; 2], ys: &[F; 2]) -> [F; 2] {
+    let eq0 = [-xs[1], F::ONE];
+    let eq1 = [-xs[0], F::ONE];
+    let e0 = eval_poly_at(&eq0, xs[0]);
+    let e1 = eval_poly_at(&eq1, xs[1]);
+    let inv_val = (e0 * e1).inv();
+    let inv_y0 = ys[0] * inv_val * e1;
+    let inv_y1 = ys[1] * inv_val * e0;
+
+    [eq0[0] * inv_y0 + eq1[0] * inv_y1,
+     eq0[1] * inv_y0 + eq1[1] * inv_y1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldElement;
+
+    fn elements(values: &[u64]) -> Vec<FieldElement> {
+        values.iter().map(|v| FieldElement::from(*v)).collect()
+    }
+
+    #[test]
+    fn test_lagrange_interp() {
+        let poly = elements(&[5, 0, 42, 300]);
+        let xs = elements(&[1, 2, 3, 4]);
+        let ys: Vec<FieldElement> = xs.iter().map(|x| eval_poly_at(&poly, *x)).collect();
+
+        assert_eq!(lagrange_interp(&xs, &ys), poly);
+        assert_eq!(eval_poly_at(&zpoly(&xs), xs[2]), FieldElement::ZERO);
+
+        let line = lagrange_interp_2(&[xs[0], xs[1]], &[ys[0], ys[1]]);
+        assert_eq!(eval_poly_at(&line, xs[0]), ys[0]);
+        assert_eq!(eval_poly_at(&line, xs[1]), ys[1]);
+    }
+
+    #[test]
+    fn test_polynomial_division() {
+        let quotient = elements(&[5, 0, 42, 300, 1]);
+
+        // by x^3 - 7
+        let seven = FieldElement::from(7u64);
+        let product = mul_polys(&quotient, &[-seven, FieldElement::ZERO, FieldElement::ZERO, FieldElement::ONE]);
+        assert_eq!(div_by_sparse(&product, 3, seven), quotient);
+
+        let divisor = zpoly(&elements(&[2, 9]));
+        assert_eq!(div_by_monic(&mul_polys(&quotient, &divisor), &divisor), quotient);
+        assert_eq!(div_by_monic(&quotient, &[FieldElement::ONE]), quotient);
+        assert!(div_by_sparse(&quotient[..2], 3, FieldElement::ONE).is_empty());
+    }
+
+    #[test]
+    fn test_multi_interp_4() {
+        let polys = elements(&[1, 2, 3, 4, 9, 0, 0, 7]);
+        let xs = elements(&[3, 5, 7, 11, 2, 4, 6, 8]);
+        let ys: Vec<FieldElement> = xs.iter().enumerate().map(|(i, x)| eval_quartic(&polys[(i / 4) * 4..(i / 4) * 4 + 4], *x)).collect();
+
+        assert_eq!(multi_interp_4(&xs, &ys), polys);
+        assert_eq!(multi_interp(&xs, &ys, 4), polys);
+    }
+
+    #[test]
+    fn test_multi_interp() {
+        for width in [2usize, 8, 16].iter() {
+            let polys: Vec<FieldElement> = (0..(3 * width) as u64).map(|i| FieldElement::from(i * i + 1)).collect();
+            let xs: Vec<FieldElement> = (0..(3 * width) as u64).map(|i| FieldElement::from(i * 3 + 2)).collect();
+            let ys: Vec<FieldElement> = xs.iter().enumerate().map(|(i, x)| eval_poly_at(&polys[(i / width) * width..(i / width + 1) * width], *x)).collect();
+
+            assert_eq!(multi_interp(&xs, &ys, *width), polys);
+        }
+    }
+
+    #[test]
+    fn test_multi_inv() {
+        let values = elements(&[3, 0, 17, 1]);
+        let invs = multi_inv(&values);
+
+        assert_eq!(invs[1], FieldElement::ZERO);
+        for (v, i) in values.iter().zip(invs.iter()).filter(|(v, _)| !v.is_zero()) {
+            assert_eq!(*v * *i, FieldElement::ONE);
+        }
+    }
 }